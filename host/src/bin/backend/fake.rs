@@ -0,0 +1,76 @@
+use std::sync::Mutex;
+
+use ream_lib::{
+    checkpoint::FinalizedCheckpoint,
+    outcome::Outcome,
+    preset::Preset,
+    protocol::{PublicOutputs, ZiskInput},
+};
+use tree_hash::Hash256;
+
+use super::{ExecutionBackend, GuestOutput, Metrics};
+use crate::report::CaseStatus;
+
+type Respond = dyn Fn(&str, &ZiskInput) -> Result<GuestOutput, CaseStatus> + Send + Sync;
+
+/// Records the inputs it is given and answers each with a canned output, without running
+/// any guest.
+pub struct FakeBackend {
+    pub inputs: Mutex<Vec<(String, Vec<u8>)>>,
+    respond: Box<Respond>,
+}
+
+impl FakeBackend {
+    /// Answer every input with the output `respond` returns for its name and decoded input.
+    pub fn new(
+        respond: impl Fn(&str, &ZiskInput) -> Result<GuestOutput, CaseStatus> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            inputs: Mutex::new(Vec::new()),
+            respond: Box::new(respond),
+        }
+    }
+}
+
+impl Default for FakeBackend {
+    /// Commits zero roots and a successful outcome for every input.
+    fn default() -> Self {
+        Self::new(|_, _| {
+            Ok(GuestOutput {
+                stdout: String::new(),
+                public_outputs: Some(PublicOutputs {
+                    pre_state_root: Hash256::ZERO,
+                    operation_root: Hash256::ZERO,
+                    post_state_root: Hash256::ZERO,
+                    deltas_digest: Hash256::ZERO,
+                    finalized_checkpoint: FinalizedCheckpoint::default(),
+                    outcome: Outcome::Success,
+                }),
+                metrics: Metrics::default(),
+            })
+        })
+    }
+}
+
+impl ExecutionBackend for FakeBackend {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    fn unit(&self) -> &'static str {
+        "steps"
+    }
+
+    fn build(&self, _preset: Preset) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn execute(&self, input_name: &str, input_bytes: &[u8]) -> Result<GuestOutput, CaseStatus> {
+        self.inputs
+            .lock()
+            .unwrap()
+            .push((input_name.to_string(), input_bytes.to_vec()));
+        let zisk_input = ZiskInput::decode(input_bytes).expect("runner encodes valid inputs");
+        (self.respond)(input_name, &zisk_input)
+    }
+}
@@ -0,0 +1,159 @@
+use ream_lib::{phase::PhaseSteps, preset::Preset, protocol::PublicOutputs};
+
+use crate::cli::backend::Backend;
+use crate::report::CaseStatus;
+
+#[cfg(test)]
+pub mod fake;
+pub mod native;
+pub mod riscv;
+pub mod zisk;
+
+/// Runs encoded guest inputs and measures their cost, e.g. in a zkVM emulator.
+pub trait ExecutionBackend: Send + Sync {
+    /// Name of the backend, as reported in logs and results.
+    fn name(&self) -> &'static str;
+
+    /// Unit of the per-phase costs the backend reports.
+    fn unit(&self) -> &'static str;
+
+    /// Build the guest program for the preset, before any input is executed.
+    fn build(&self, preset: Preset) -> Result<(), String>;
+
+    /// Execute an encoded `ZiskInput`, returning the outputs committed by the guest along
+    /// with the measured metrics. `input_name` is unique per run of a test case.
+    fn execute(&self, input_name: &str, input_bytes: &[u8]) -> Result<GuestOutput, CaseStatus>;
+
+    /// SHA-256 of the built guest program, if the backend runs one.
+    fn program_hash(&self) -> Option<String> {
+        None
+    }
+}
+
+impl From<Backend> for Box<dyn ExecutionBackend> {
+    fn from(backend: Backend) -> Self {
+        match backend {
+            Backend::Zisk => Box::new(zisk::ZiskBackend::default()),
+            Backend::Native => Box::new(native::NativeBackend),
+            Backend::Riscv => Box::new(riscv::RiscvBackend::default()),
+        }
+    }
+}
+
+/// Output of a single run of the guest logic.
+#[derive(Debug, Clone)]
+pub struct GuestOutput {
+    /// Standard output of the guest, holding the marker lines it reports.
+    pub stdout: String,
+    /// Public outputs committed by the guest, if it got to commit them.
+    pub public_outputs: Option<PublicOutputs>,
+    pub metrics: Metrics,
+}
+
+/// Cost of a single run of the guest logic.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    /// Cost per guest phase, in the unit of the backend.
+    pub phase_costs: PhaseSteps,
+    /// Total steps of the run, for backends counting steps.
+    pub steps: Option<u64>,
+    pub duration_secs: Option<f64>,
+    /// Allocations per guest phase, for backends running on the host.
+    pub allocations: Option<PhaseSteps>,
+    pub allocated_bytes: Option<PhaseSteps>,
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_lib::{
+        checkpoint::FinalizedCheckpoint,
+        fork::ForkType,
+        input::{OperationInput, SlotsWrapper},
+        outcome::Outcome,
+        phase::{format_phase_steps, Phase},
+        protocol::ZiskInput,
+    };
+    use tree_hash::Hash256;
+
+    use super::{fake::FakeBackend, *};
+
+    #[test]
+    fn run_guest_executes_the_encoded_input() {
+        let backend = FakeBackend::default();
+        let input = OperationInput::Slots(SlotsWrapper { slots: 3 });
+
+        let output =
+            crate::run_guest(&backend, ForkType::Fulu, &[1, 2, 3], &input, "slots_case").unwrap();
+
+        assert_eq!(
+            output.public_outputs.map(|outputs| outputs.outcome),
+            Some(Outcome::Success)
+        );
+        let inputs = backend.inputs.lock().unwrap();
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs[0].0, "slots_case");
+        let zisk_input = ZiskInput::decode(&inputs[0].1).unwrap();
+        assert_eq!(zisk_input.fork().unwrap(), ForkType::Fulu);
+        assert_eq!(zisk_input.pre_state_ssz_bytes, vec![1, 2, 3]);
+        assert!(matches!(
+            zisk_input.operation().unwrap(),
+            OperationInput::Slots(SlotsWrapper { slots: 3 })
+        ));
+    }
+
+    #[test]
+    fn native_backend_reports_a_guest_panic_for_an_undecodable_input() {
+        let result = native::NativeBackend.execute("garbage", &[0xff; 3]);
+
+        assert!(matches!(result, Err(CaseStatus::GuestPanic(_))));
+    }
+
+    #[test]
+    fn zisk_backend_reads_the_committed_outputs_and_metrics() {
+        let public_outputs = PublicOutputs {
+            pre_state_root: Hash256::repeat_byte(0x11),
+            operation_root: Hash256::repeat_byte(0x22),
+            post_state_root: Hash256::repeat_byte(0x33),
+            deltas_digest: Hash256::ZERO,
+            finalized_checkpoint: FinalizedCheckpoint::default(),
+            outcome: Outcome::Rejected,
+        };
+        let stdout: Vec<String> = [
+            format_phase_steps(Phase::Process, 100),
+            format_phase_steps(Phase::Merkleize, 20),
+            format_phase_steps(Phase::Merkleize, 22),
+        ]
+        .into_iter()
+        .chain(
+            public_outputs
+                .to_words()
+                .iter()
+                .map(|word| format!("{word:08x}")),
+        )
+        .chain(["process_rom() steps=1234 duration=0.5000 tp=2.4680 Msteps/s".to_string()])
+        .collect();
+
+        let output = zisk::read_output(stdout.join("\n"));
+
+        assert_eq!(output.public_outputs, Some(public_outputs));
+        assert_eq!(output.metrics.phase_costs.get(Phase::Process), 100);
+        assert_eq!(output.metrics.phase_costs.get(Phase::Merkleize), 42);
+        assert_eq!(output.metrics.steps, Some(1234));
+        assert_eq!(output.metrics.duration_secs, Some(0.5));
+        assert!(output.metrics.allocations.is_none());
+    }
+
+    #[test]
+    fn zisk_backend_reports_a_guest_panic_printed_to_the_uart() {
+        let stdout = "panicked at guest/src/main.rs:1:1:\nFailed to deserialize input\n";
+
+        assert!(matches!(
+            zisk::failure_status(stdout, "Error: emulation halted\n"),
+            CaseStatus::GuestPanic(message) if message.contains("Failed to deserialize input")
+        ));
+        assert!(matches!(
+            zisk::failure_status("", "Error: emulation halted\n"),
+            CaseStatus::EmulatorError(_)
+        ));
+    }
+}
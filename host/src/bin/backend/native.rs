@@ -0,0 +1,129 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::any::Any;
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Instant;
+
+use ream_lib::{
+    execute::{execute, PhaseMeter},
+    phase::{Phase, PhaseSteps},
+    preset::Preset,
+};
+
+use super::{ExecutionBackend, GuestOutput, Metrics};
+use crate::report::CaseStatus;
+
+/// Counts the allocations of every thread on top of the system allocator.
+struct CountingAllocator;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+thread_local! {
+    // Const-initialized, so that reading them from the allocator does not allocate.
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+    static ALLOCATED_BYTES: Cell<u64> = const { Cell::new(0) };
+}
+
+fn record_allocation(bytes: usize) {
+    // Allocations made while the thread is torn down are not counted
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+    let _ = ALLOCATED_BYTES.try_with(|count| count.set(count.get() + bytes as u64));
+}
+
+/// Allocations and allocated bytes of the current thread so far.
+fn allocation_counts() -> (u64, u64) {
+    (ALLOCATIONS.with(Cell::get), ALLOCATED_BYTES.with(Cell::get))
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record_allocation(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record_allocation(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record_allocation(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+/// Per-phase costs of a native run. Allocations are counted per thread, so concurrent
+/// test cases do not count each other's.
+#[derive(Debug, Clone, Default)]
+struct NativeMeter {
+    wall_nanos: PhaseSteps,
+    allocations: PhaseSteps,
+    allocated_bytes: PhaseSteps,
+}
+
+impl PhaseMeter for NativeMeter {
+    fn measure<T>(&mut self, phase: Phase, f: impl FnOnce() -> T) -> T {
+        let (start_allocations, start_bytes) = allocation_counts();
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+        let (end_allocations, end_bytes) = allocation_counts();
+
+        self.wall_nanos.add(phase, elapsed.as_nanos() as u64);
+        self.allocations
+            .add(phase, end_allocations - start_allocations);
+        self.allocated_bytes.add(phase, end_bytes - start_bytes);
+        result
+    }
+}
+
+/// Runs the guest logic linked into the host, measuring the wall-clock time and the
+/// allocations of every phase.
+pub struct NativeBackend;
+
+impl ExecutionBackend for NativeBackend {
+    fn name(&self) -> &'static str {
+        "native"
+    }
+
+    fn unit(&self) -> &'static str {
+        "ns"
+    }
+
+    fn build(&self, _preset: Preset) -> Result<(), String> {
+        // The guest logic is compiled into the host, for the preset the host is built for
+        Ok(())
+    }
+
+    fn execute(&self, _input_name: &str, input_bytes: &[u8]) -> Result<GuestOutput, CaseStatus> {
+        let mut meter = NativeMeter::default();
+        let execution = panic::catch_unwind(AssertUnwindSafe(|| execute(input_bytes, &mut meter)))
+            .map_err(|panic| CaseStatus::GuestPanic(panic_message(panic.as_ref())))?;
+
+        let public_outputs = meter.measure(Phase::Output, || execution.public_outputs());
+        Ok(GuestOutput {
+            stdout: String::new(),
+            public_outputs: Some(public_outputs),
+            metrics: Metrics {
+                steps: None,
+                duration_secs: Some(meter.wall_nanos.total() as f64 / 1e9),
+                phase_costs: meter.wall_nanos,
+                allocations: Some(meter.allocations),
+                allocated_bytes: Some(meter.allocated_bytes),
+            },
+        })
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "native run panicked".to_string())
+}
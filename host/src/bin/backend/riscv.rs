@@ -0,0 +1,267 @@
+use std::fs;
+use std::time::Instant;
+
+use ream_lib::{phase::PhaseSteps, preset::Preset, protocol::PublicOutputs};
+
+use super::{zisk::ZiskBackend, ExecutionBackend, GuestOutput, Metrics};
+use crate::report::CaseStatus;
+
+#[cfg(test)]
+mod asm;
+mod cpu;
+mod elf;
+
+use cpu::{Cpu, Memory};
+
+// Memory layout and system calls of the ZisK runtime (`ziskos`), which the interpreter
+// serves in place of the emulator.
+/// Input area: the input length is stored at offset 8 and the input bytes from offset 16.
+const INPUT_ADDR: u64 = 0x9000_0000;
+/// Output area: the number of public output words, followed by the words.
+const OUTPUT_ADDR: u64 = 0xa001_0000;
+/// Byte port the runtime prints to.
+const UART_ADDR: u64 = 0xa000_0200;
+/// Most public output words the output area holds.
+const MAX_OUTPUT_WORDS: u32 = 64;
+const SYS_WRITE: u64 = 64;
+const SYS_EXIT: u64 = 93;
+
+/// Registers of the system call ABI.
+const A0: usize = 10;
+const A1: usize = 11;
+const A2: usize = 12;
+const A7: usize = 17;
+
+/// Retired instructions after which a run is stopped, so a guest stuck in a loop fails
+/// instead of hanging the run.
+const MAX_INSTRUCTIONS: u64 = 1 << 36;
+
+/// Runs the guest ELF on a local rv64ima interpreter, counting retired instructions.
+///
+/// The ELF is the one `cargo-zisk` builds. The interpreter serves the input, output and
+/// console of the ZisK runtime but none of its precompiles, so the counts are the plain
+/// RISC-V cost of the guest logic.
+pub struct RiscvBackend {
+    /// Builds the guest ELF and locates it.
+    pub zisk: ZiskBackend,
+    pub max_instructions: u64,
+}
+
+impl Default for RiscvBackend {
+    fn default() -> Self {
+        Self {
+            zisk: ZiskBackend::default(),
+            max_instructions: MAX_INSTRUCTIONS,
+        }
+    }
+}
+
+impl ExecutionBackend for RiscvBackend {
+    fn name(&self) -> &'static str {
+        "riscv"
+    }
+
+    fn unit(&self) -> &'static str {
+        "instructions"
+    }
+
+    fn build(&self, preset: Preset) -> Result<(), String> {
+        self.zisk.build(preset)
+    }
+
+    fn execute(&self, _input_name: &str, input_bytes: &[u8]) -> Result<GuestOutput, CaseStatus> {
+        let elf = fs::read(&self.zisk.guest_elf).map_err(|e| {
+            CaseStatus::EmulatorError(format!(
+                "failed to read guest ELF {:?}: {e}",
+                self.zisk.guest_elf
+            ))
+        })?;
+        let start = Instant::now();
+        let run =
+            run_elf(&elf, input_bytes, self.max_instructions).map_err(CaseStatus::EmulatorError)?;
+        let duration_secs = start.elapsed().as_secs_f64();
+
+        match run.exit {
+            Ok(0) => {}
+            // The runtime prints the panic message before halting
+            Ok(code) => {
+                return Err(CaseStatus::GuestPanic(format!(
+                    "exit code {code}: {}",
+                    run.console
+                )))
+            }
+            Err(e) if run.console.contains("panicked") => {
+                return Err(CaseStatus::GuestPanic(format!("{e}: {}", run.console)))
+            }
+            Err(e) => return Err(CaseStatus::EmulatorError(e)),
+        }
+        Ok(GuestOutput {
+            public_outputs: PublicOutputs::from_words(&run.output_words),
+            metrics: Metrics {
+                phase_costs: PhaseSteps::parse(&run.console),
+                steps: Some(run.instructions),
+                duration_secs: Some(duration_secs),
+                allocations: None,
+                allocated_bytes: None,
+            },
+            stdout: run.console,
+        })
+    }
+
+    fn program_hash(&self) -> Option<String> {
+        self.zisk.program_hash()
+    }
+}
+
+/// State of an interpreted guest once it stopped.
+struct GuestRun {
+    /// Exit code the guest halted with, or the reason it was stopped.
+    exit: Result<u64, String>,
+    console: String,
+    output_words: Vec<u32>,
+    instructions: u64,
+}
+
+/// Load the guest ELF and its input, then run it until it halts.
+fn run_elf(elf: &[u8], input: &[u8], max_instructions: u64) -> Result<GuestRun, String> {
+    let mut memory = Memory::default();
+    let entry = elf::load_elf(elf, &mut memory)?;
+    memory
+        .write(INPUT_ADDR + 8, &(input.len() as u64).to_le_bytes())
+        .and_then(|()| memory.write(INPUT_ADDR + 16, input))
+        .map_err(|_| format!("input of {} bytes does not fit in memory", input.len()))?;
+
+    let mut cpu = Cpu::new(memory, entry);
+    cpu.console_address = Some(UART_ADDR);
+    let exit = loop {
+        if let Err(trap) = cpu.run_until_ecall(max_instructions) {
+            break Err(trap.to_string());
+        }
+        match cpu.regs[A7] {
+            SYS_EXIT => break Ok(cpu.regs[A0]),
+            SYS_WRITE => {
+                let (address, len) = (cpu.regs[A1], cpu.regs[A2]);
+                if write_console(&mut cpu, address, len).is_err() {
+                    break Err(format!(
+                        "write of {len} bytes from {address:#x} out of range"
+                    ));
+                }
+                cpu.regs[A0] = len;
+            }
+            call => {
+                break Err(format!(
+                    "unsupported system call {call}, zkVM precompiles are not available"
+                ))
+            }
+        }
+    };
+
+    let output_words = output_words(&cpu.memory)?;
+    Ok(GuestRun {
+        exit,
+        console: String::from_utf8_lossy(&cpu.console).into_owned(),
+        output_words,
+        instructions: cpu.instret,
+    })
+}
+
+/// Append `len` guest bytes from `address` to the console. The range is checked before
+/// anything is copied, so a bogus length faults instead of being allocated.
+fn write_console(cpu: &mut Cpu, address: u64, len: u64) -> Result<(), u64> {
+    cpu::check_range(address, usize::try_from(len).map_err(|_| address)?)?;
+    let mut chunk = [0; 4096];
+    let mut done = 0;
+    while done < len {
+        let size = (len - done).min(chunk.len() as u64) as usize;
+        cpu.memory.read(address + done, &mut chunk[..size])?;
+        cpu.console.extend_from_slice(&chunk[..size]);
+        done += size as u64;
+    }
+    Ok(())
+}
+
+/// Public output words the guest set in the output area.
+fn output_words(memory: &Memory) -> Result<Vec<u32>, String> {
+    let count = memory
+        .read_u32(OUTPUT_ADDR)
+        .map_err(|_| "output area out of range".to_string())?;
+    if count > MAX_OUTPUT_WORDS {
+        return Err(format!(
+            "{count} public output words set, at most {MAX_OUTPUT_WORDS} fit"
+        ));
+    }
+    (0..count as u64)
+        .map(|i| {
+            memory
+                .read_u32(OUTPUT_ADDR + 4 + 4 * i)
+                .map_err(|_| "output area out of range".to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::asm::*;
+    use super::{run_elf, INPUT_ADDR, OUTPUT_ADDR, UART_ADDR};
+
+    #[test]
+    fn serves_the_input_output_and_console_of_the_runtime() {
+        let program = [
+            li_address(T1, INPUT_ADDR).as_slice(),
+            &[ld(T0, T1, 8), lbu(T2, T1, 16)],
+            &li_address(A0, OUTPUT_ADDR),
+            &[
+                addi(A1, ZERO, 2),
+                sw(A1, A0, 0),
+                sw(T0, A0, 4),
+                sw(T2, A0, 8),
+            ],
+            &li_address(A2, UART_ADDR - 0x200),
+            &[
+                addi(A1, ZERO, b'k' as i32),
+                sb(A1, A2, 0x200),
+                rdinstret(A0),
+                addi(A7, ZERO, 93),
+                ECALL,
+            ],
+        ]
+        .concat();
+
+        let run = run_elf(&elf(&program), &[7, 8, 9], 1000).unwrap();
+
+        assert_eq!(run.exit, Ok(17));
+        assert_eq!(run.output_words, [3, 7]);
+        assert_eq!(run.console, "k");
+        assert_eq!(run.instructions, 20);
+    }
+
+    #[test]
+    fn stops_on_system_calls_the_runtime_does_not_serve() {
+        let program = [addi(A7, ZERO, 0x7f), ECALL];
+
+        let run = run_elf(&elf(&program), &[], 1000).unwrap();
+
+        let error = run.exit.unwrap_err();
+        assert!(error.contains("precompiles are not available"), "{error}");
+        assert!(run.output_words.is_empty());
+    }
+
+    #[test]
+    fn stops_on_writes_out_of_the_address_space() {
+        let program = [
+            li_address(A1, UART_ADDR - 0x200).as_slice(),
+            &[
+                addi(A2, ZERO, -1),
+                addi(A0, ZERO, 1),
+                addi(A7, ZERO, 64),
+                ECALL,
+            ],
+        ]
+        .concat();
+
+        let run = run_elf(&elf(&program), &[], 1000).unwrap();
+
+        let error = run.exit.unwrap_err();
+        assert!(error.contains("out of range"), "{error}");
+    }
+}
@@ -0,0 +1,202 @@
+// Encoders for the instructions the interpreter tests run, and a minimal ELF around them
+
+pub const ZERO: u32 = 0;
+pub const T0: u32 = 5;
+pub const T1: u32 = 6;
+pub const T2: u32 = 7;
+pub const A0: u32 = 10;
+pub const A1: u32 = 11;
+pub const A2: u32 = 12;
+pub const A7: u32 = 17;
+
+/// Address the test ELF loads its code at.
+pub const CODE_ADDR: u64 = 0x8000_0000;
+
+fn r_type(funct7: u32, rs2: u32, rs1: u32, funct3: u32, rd: u32, opcode: u32) -> u32 {
+    (funct7 << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+}
+
+fn i_type(imm: i32, rs1: u32, funct3: u32, rd: u32, opcode: u32) -> u32 {
+    ((imm as u32 & 0xfff) << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+}
+
+fn s_type(imm: i32, rs2: u32, rs1: u32, funct3: u32) -> u32 {
+    let imm = imm as u32;
+    (((imm >> 5) & 0x7f) << 25)
+        | (rs2 << 20)
+        | (rs1 << 15)
+        | (funct3 << 12)
+        | ((imm & 0x1f) << 7)
+        | 0x23
+}
+
+pub fn addi(rd: u32, rs1: u32, imm: i32) -> u32 {
+    i_type(imm, rs1, 0, rd, 0x13)
+}
+
+pub fn slli(rd: u32, rs1: u32, shamt: u32) -> u32 {
+    i_type(shamt as i32, rs1, 1, rd, 0x13)
+}
+
+pub fn srli(rd: u32, rs1: u32, shamt: u32) -> u32 {
+    i_type(shamt as i32, rs1, 5, rd, 0x13)
+}
+
+/// Load `upper << 12`, sign-extended, into `rd`.
+pub fn lui(rd: u32, upper: u32) -> u32 {
+    (upper << 12) | (rd << 7) | 0x37
+}
+
+/// Load a 32-bit address with its low 12 bits clear into `rd`, zero-extended.
+pub fn li_address(rd: u32, address: u64) -> [u32; 3] {
+    assert_eq!(address & 0xfff, 0);
+    [
+        lui(rd, (address >> 12) as u32),
+        slli(rd, rd, 32),
+        srli(rd, rd, 32),
+    ]
+}
+
+pub fn add(rd: u32, rs1: u32, rs2: u32) -> u32 {
+    r_type(0, rs2, rs1, 0, rd, 0x33)
+}
+
+pub fn sub(rd: u32, rs1: u32, rs2: u32) -> u32 {
+    r_type(0x20, rs2, rs1, 0, rd, 0x33)
+}
+
+pub fn mul(rd: u32, rs1: u32, rs2: u32) -> u32 {
+    r_type(1, rs2, rs1, 0, rd, 0x33)
+}
+
+pub fn mulhu(rd: u32, rs1: u32, rs2: u32) -> u32 {
+    r_type(1, rs2, rs1, 3, rd, 0x33)
+}
+
+pub fn div(rd: u32, rs1: u32, rs2: u32) -> u32 {
+    r_type(1, rs2, rs1, 4, rd, 0x33)
+}
+
+pub fn remu(rd: u32, rs1: u32, rs2: u32) -> u32 {
+    r_type(1, rs2, rs1, 7, rd, 0x33)
+}
+
+pub fn addw(rd: u32, rs1: u32, rs2: u32) -> u32 {
+    r_type(0, rs2, rs1, 0, rd, 0x3b)
+}
+
+pub fn ld(rd: u32, rs1: u32, imm: i32) -> u32 {
+    i_type(imm, rs1, 3, rd, 0x03)
+}
+
+pub fn lbu(rd: u32, rs1: u32, imm: i32) -> u32 {
+    i_type(imm, rs1, 4, rd, 0x03)
+}
+
+pub fn lw(rd: u32, rs1: u32, imm: i32) -> u32 {
+    i_type(imm, rs1, 2, rd, 0x03)
+}
+
+pub fn sb(rs2: u32, rs1: u32, imm: i32) -> u32 {
+    s_type(imm, rs2, rs1, 0)
+}
+
+pub fn sw(rs2: u32, rs1: u32, imm: i32) -> u32 {
+    s_type(imm, rs2, rs1, 2)
+}
+
+pub fn sd(rs2: u32, rs1: u32, imm: i32) -> u32 {
+    s_type(imm, rs2, rs1, 3)
+}
+
+/// Branch if `rs1 != rs2`, by `offset` bytes.
+pub fn bne(rs1: u32, rs2: u32, offset: i32) -> u32 {
+    let imm = offset as u32;
+    (((imm >> 12) & 0x1) << 31)
+        | (((imm >> 5) & 0x3f) << 25)
+        | (rs2 << 20)
+        | (rs1 << 15)
+        | (1 << 12)
+        | (((imm >> 1) & 0xf) << 8)
+        | (((imm >> 11) & 0x1) << 7)
+        | 0x63
+}
+
+/// Jump by `offset` bytes, linking into `rd`.
+pub fn jal(rd: u32, offset: i32) -> u32 {
+    let imm = offset as u32;
+    (((imm >> 20) & 0x1) << 31)
+        | (((imm >> 1) & 0x3ff) << 21)
+        | (((imm >> 11) & 0x1) << 20)
+        | (imm & 0x000f_f000)
+        | (rd << 7)
+        | 0x6f
+}
+
+pub fn amoadd_d(rd: u32, rs2: u32, rs1: u32) -> u32 {
+    r_type(0, rs2, rs1, 3, rd, 0x2f)
+}
+
+pub fn lr_d(rd: u32, rs1: u32) -> u32 {
+    r_type(0x02 << 2, 0, rs1, 3, rd, 0x2f)
+}
+
+pub fn sc_d(rd: u32, rs2: u32, rs1: u32) -> u32 {
+    r_type(0x03 << 2, rs2, rs1, 3, rd, 0x2f)
+}
+
+pub fn csrrs(rd: u32, csr: u32, rs1: u32) -> u32 {
+    (csr << 20) | (rs1 << 15) | (2 << 12) | (rd << 7) | 0x73
+}
+
+pub fn rdinstret(rd: u32) -> u32 {
+    csrrs(rd, 0xc02, ZERO)
+}
+
+pub const ECALL: u32 = 0x0000_0073;
+
+/// A 64-bit RISC-V ELF with the instructions as its single segment at `CODE_ADDR`, which is
+/// also its entry point.
+pub fn elf(instructions: &[u32]) -> Vec<u8> {
+    const HEADER_SIZE: usize = 64;
+    const PROGRAM_HEADER_SIZE: usize = 56;
+    let code: Vec<u8> = instructions
+        .iter()
+        .flat_map(|inst| inst.to_le_bytes())
+        .collect();
+    let code_offset = (HEADER_SIZE + PROGRAM_HEADER_SIZE) as u64;
+
+    let mut elf = Vec::new();
+    elf.extend_from_slice(b"\x7fELF");
+    // 64-bit, little-endian, version 1
+    elf.extend_from_slice(&[2, 1, 1]);
+    elf.resize(16, 0);
+    // Executable, RISC-V, version 1
+    elf.extend_from_slice(&2u16.to_le_bytes());
+    elf.extend_from_slice(&243u16.to_le_bytes());
+    elf.extend_from_slice(&1u32.to_le_bytes());
+    elf.extend_from_slice(&CODE_ADDR.to_le_bytes());
+    // Program headers right after the header, no section headers
+    elf.extend_from_slice(&(HEADER_SIZE as u64).to_le_bytes());
+    elf.extend_from_slice(&0u64.to_le_bytes());
+    elf.extend_from_slice(&0u32.to_le_bytes());
+    elf.extend_from_slice(&(HEADER_SIZE as u16).to_le_bytes());
+    elf.extend_from_slice(&(PROGRAM_HEADER_SIZE as u16).to_le_bytes());
+    elf.extend_from_slice(&1u16.to_le_bytes());
+    elf.extend_from_slice(&[0; 6]);
+    assert_eq!(elf.len(), HEADER_SIZE);
+
+    // A readable, executable PT_LOAD segment with a zeroed tail
+    elf.extend_from_slice(&1u32.to_le_bytes());
+    elf.extend_from_slice(&5u32.to_le_bytes());
+    elf.extend_from_slice(&code_offset.to_le_bytes());
+    elf.extend_from_slice(&CODE_ADDR.to_le_bytes());
+    elf.extend_from_slice(&CODE_ADDR.to_le_bytes());
+    elf.extend_from_slice(&(code.len() as u64).to_le_bytes());
+    elf.extend_from_slice(&(code.len() as u64 + 16).to_le_bytes());
+    elf.extend_from_slice(&4u64.to_le_bytes());
+    assert_eq!(elf.len() as u64, code_offset);
+
+    elf.extend_from_slice(&code);
+    elf
+}
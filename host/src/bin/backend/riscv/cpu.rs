@@ -0,0 +1,648 @@
+use std::fmt;
+
+/// Bits of the address within a memory page.
+const PAGE_BITS: u32 = 16;
+const PAGE_SIZE: usize = 1 << PAGE_BITS;
+/// Memory covers the 32-bit address space, which holds every region of the guest layout.
+const ADDRESS_LIMIT: u64 = 1 << 32;
+
+/// Counter CSRs the guest may read, e.g. through `rdinstret`.
+const CSR_CYCLE: u16 = 0xc00;
+const CSR_TIME: u16 = 0xc01;
+const CSR_INSTRET: u16 = 0xc02;
+
+/// Zero-initialized memory, allocated a page at a time as the guest writes to it.
+pub struct Memory {
+    pages: Vec<Option<Box<[u8]>>>,
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Self {
+            pages: (0..ADDRESS_LIMIT >> PAGE_BITS).map(|_| None).collect(),
+        }
+    }
+}
+
+impl Memory {
+    /// Copy `buf.len()` bytes from `address` into `buf`. Returns the faulting address if the
+    /// range leaves the address space.
+    pub fn read(&self, address: u64, buf: &mut [u8]) -> Result<(), u64> {
+        check_range(address, buf.len())?;
+        let mut done = 0;
+        while done < buf.len() {
+            let current = address + done as u64;
+            let offset = (current as usize) & (PAGE_SIZE - 1);
+            let len = (PAGE_SIZE - offset).min(buf.len() - done);
+            match &self.pages[(current >> PAGE_BITS) as usize] {
+                Some(page) => buf[done..done + len].copy_from_slice(&page[offset..offset + len]),
+                None => buf[done..done + len].fill(0),
+            }
+            done += len;
+        }
+        Ok(())
+    }
+
+    /// Copy `bytes` to `address`. Returns the faulting address if the range leaves the
+    /// address space.
+    pub fn write(&mut self, address: u64, bytes: &[u8]) -> Result<(), u64> {
+        check_range(address, bytes.len())?;
+        let mut done = 0;
+        while done < bytes.len() {
+            let current = address + done as u64;
+            let offset = (current as usize) & (PAGE_SIZE - 1);
+            let len = (PAGE_SIZE - offset).min(bytes.len() - done);
+            let page = self.pages[(current >> PAGE_BITS) as usize]
+                .get_or_insert_with(|| vec![0; PAGE_SIZE].into_boxed_slice());
+            page[offset..offset + len].copy_from_slice(&bytes[done..done + len]);
+            done += len;
+        }
+        Ok(())
+    }
+
+    fn load<const N: usize>(&self, address: u64) -> Result<[u8; N], u64> {
+        let mut bytes = [0; N];
+        self.read(address, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    pub fn read_u32(&self, address: u64) -> Result<u32, u64> {
+        self.load(address).map(u32::from_le_bytes)
+    }
+}
+
+/// Check that `len` bytes from `address` lie in the address space, returning `address` if not.
+pub fn check_range(address: u64, len: usize) -> Result<(), u64> {
+    match address.checked_add(len as u64) {
+        Some(end) if end <= ADDRESS_LIMIT => Ok(()),
+        _ => Err(address),
+    }
+}
+
+/// Reason the interpreter stopped executing the guest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trap {
+    /// The instruction is not part of rv64ima.
+    IllegalInstruction {
+        pc: u64,
+        instruction: u32,
+    },
+    /// A CSR other than the read-only counters was accessed, as zkVM precompiles do.
+    UnsupportedCsr {
+        pc: u64,
+        csr: u16,
+    },
+    /// A memory access left the address space.
+    MemoryFault {
+        pc: u64,
+        address: u64,
+    },
+    /// The program counter is not aligned to an instruction.
+    MisalignedPc {
+        pc: u64,
+    },
+    Breakpoint {
+        pc: u64,
+    },
+    /// The guest retired more instructions than it is allowed to.
+    InstructionLimit {
+        limit: u64,
+    },
+}
+
+impl fmt::Display for Trap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trap::IllegalInstruction { pc, instruction } => {
+                write!(f, "illegal instruction {instruction:#010x} at {pc:#x}")
+            }
+            Trap::UnsupportedCsr { pc, csr } => write!(
+                f,
+                "unsupported CSR {csr:#05x} at {pc:#x}, zkVM precompiles are not available"
+            ),
+            Trap::MemoryFault { pc, address } => {
+                write!(f, "memory access at {address:#x} out of range at {pc:#x}")
+            }
+            Trap::MisalignedPc { pc } => write!(f, "misaligned program counter {pc:#x}"),
+            Trap::Breakpoint { pc } => write!(f, "breakpoint at {pc:#x}"),
+            Trap::InstructionLimit { limit } => {
+                write!(f, "instruction limit of {limit} reached")
+            }
+        }
+    }
+}
+
+/// Single-hart rv64ima interpreter without privileged modes.
+pub struct Cpu {
+    pub regs: [u64; 32],
+    pub pc: u64,
+    pub memory: Memory,
+    /// Number of retired instructions, as read through the `instret` CSR.
+    pub instret: u64,
+    /// Address of a byte-wide output port. Bytes stored to it are appended to `console`
+    /// instead of memory.
+    pub console_address: Option<u64>,
+    pub console: Vec<u8>,
+    /// Address reserved by the last load-reserved instruction.
+    reservation: Option<u64>,
+}
+
+impl Cpu {
+    pub fn new(memory: Memory, pc: u64) -> Self {
+        Self {
+            regs: [0; 32],
+            pc,
+            memory,
+            instret: 0,
+            console_address: None,
+            console: Vec::new(),
+            reservation: None,
+        }
+    }
+
+    /// Execute instructions until an `ecall` retires, leaving the program counter after it.
+    /// Fails once `limit` instructions have retired.
+    pub fn run_until_ecall(&mut self, limit: u64) -> Result<(), Trap> {
+        loop {
+            if self.instret >= limit {
+                return Err(Trap::InstructionLimit { limit });
+            }
+            let ecall = self.step()?;
+            self.instret += 1;
+            if ecall {
+                return Ok(());
+            }
+        }
+    }
+
+    fn set(&mut self, rd: usize, value: u64) {
+        if rd != 0 {
+            self.regs[rd] = value;
+        }
+    }
+
+    fn fault(&self, address: u64) -> Trap {
+        Trap::MemoryFault {
+            pc: self.pc,
+            address,
+        }
+    }
+
+    fn load<const N: usize>(&self, address: u64) -> Result<[u8; N], Trap> {
+        self.memory
+            .load(address)
+            .map_err(|address| self.fault(address))
+    }
+
+    fn store(&mut self, address: u64, bytes: &[u8]) -> Result<(), Trap> {
+        if self.console_address == Some(address) {
+            self.console.push(bytes[0]);
+            return Ok(());
+        }
+        self.memory
+            .write(address, bytes)
+            .map_err(|address| self.fault(address))
+    }
+
+    /// Execute the instruction at the program counter, returning whether it was an `ecall`.
+    fn step(&mut self) -> Result<bool, Trap> {
+        let pc = self.pc;
+        if pc & 3 != 0 {
+            return Err(Trap::MisalignedPc { pc });
+        }
+        let inst = u32::from_le_bytes(self.load(pc)?);
+        let illegal = Trap::IllegalInstruction {
+            pc,
+            instruction: inst,
+        };
+
+        let rd = ((inst >> 7) & 0x1f) as usize;
+        let funct3 = (inst >> 12) & 0x7;
+        let rs1 = ((inst >> 15) & 0x1f) as usize;
+        let rs2 = ((inst >> 20) & 0x1f) as usize;
+        let funct7 = inst >> 25;
+        let a = self.regs[rs1];
+        let b = self.regs[rs2];
+        let imm_i = ((inst as i32) >> 20) as i64 as u64;
+
+        let mut next_pc = pc.wrapping_add(4);
+        let mut ecall = false;
+        match inst & 0x7f {
+            // LUI
+            0x37 => self.set(rd, (inst & 0xffff_f000) as i32 as i64 as u64),
+            // AUIPC
+            0x17 => self.set(
+                rd,
+                pc.wrapping_add((inst & 0xffff_f000) as i32 as i64 as u64),
+            ),
+            // JAL
+            0x6f => {
+                self.set(rd, next_pc);
+                next_pc = pc.wrapping_add(imm_j(inst));
+            }
+            // JALR
+            0x67 if funct3 == 0 => {
+                self.set(rd, next_pc);
+                next_pc = a.wrapping_add(imm_i) & !1;
+            }
+            // Branches
+            0x63 => {
+                let taken = match funct3 {
+                    0 => a == b,
+                    1 => a != b,
+                    4 => (a as i64) < (b as i64),
+                    5 => (a as i64) >= (b as i64),
+                    6 => a < b,
+                    7 => a >= b,
+                    _ => return Err(illegal),
+                };
+                if taken {
+                    next_pc = pc.wrapping_add(imm_b(inst));
+                }
+            }
+            // Loads
+            0x03 => {
+                let address = a.wrapping_add(imm_i);
+                let value = match funct3 {
+                    0 => i8::from_le_bytes(self.load(address)?) as i64 as u64,
+                    1 => i16::from_le_bytes(self.load(address)?) as i64 as u64,
+                    2 => i32::from_le_bytes(self.load(address)?) as i64 as u64,
+                    3 => u64::from_le_bytes(self.load(address)?),
+                    4 => u8::from_le_bytes(self.load(address)?) as u64,
+                    5 => u16::from_le_bytes(self.load(address)?) as u64,
+                    6 => u32::from_le_bytes(self.load(address)?) as u64,
+                    _ => return Err(illegal),
+                };
+                self.set(rd, value);
+            }
+            // Stores
+            0x23 => {
+                let address = a.wrapping_add(imm_s(inst));
+                let bytes = b.to_le_bytes();
+                match funct3 {
+                    0..=3 => self.store(address, &bytes[..1 << funct3])?,
+                    _ => return Err(illegal),
+                }
+            }
+            // Register-immediate operations
+            0x13 => {
+                let shamt = (inst >> 20) & 0x3f;
+                let value = match (funct3, inst >> 26) {
+                    (0, _) => a.wrapping_add(imm_i),
+                    (1, 0) => a << shamt,
+                    (2, _) => ((a as i64) < (imm_i as i64)) as u64,
+                    (3, _) => (a < imm_i) as u64,
+                    (4, _) => a ^ imm_i,
+                    (5, 0) => a >> shamt,
+                    (5, 0x10) => ((a as i64) >> shamt) as u64,
+                    (6, _) => a | imm_i,
+                    (7, _) => a & imm_i,
+                    _ => return Err(illegal),
+                };
+                self.set(rd, value);
+            }
+            // Register-register operations
+            0x33 => {
+                let value = match (funct7, funct3) {
+                    (0, 0) => a.wrapping_add(b),
+                    (0x20, 0) => a.wrapping_sub(b),
+                    (0, 1) => a << (b & 0x3f),
+                    (0, 2) => ((a as i64) < (b as i64)) as u64,
+                    (0, 3) => (a < b) as u64,
+                    (0, 4) => a ^ b,
+                    (0, 5) => a >> (b & 0x3f),
+                    (0x20, 5) => ((a as i64) >> (b & 0x3f)) as u64,
+                    (0, 6) => a | b,
+                    (0, 7) => a & b,
+                    (1, funct3) => multiply_divide(funct3, a, b),
+                    _ => return Err(illegal),
+                };
+                self.set(rd, value);
+            }
+            // 32-bit register-immediate operations
+            0x1b => {
+                let shamt = (inst >> 20) & 0x1f;
+                let a32 = a as u32;
+                let value = match (funct3, funct7) {
+                    (0, _) => a32.wrapping_add(imm_i as u32),
+                    (1, 0) => a32 << shamt,
+                    (5, 0) => a32 >> shamt,
+                    (5, 0x20) => ((a32 as i32) >> shamt) as u32,
+                    _ => return Err(illegal),
+                };
+                self.set(rd, value as i32 as i64 as u64);
+            }
+            // 32-bit register-register operations
+            0x3b => {
+                let (a32, b32) = (a as u32, b as u32);
+                let value = match (funct7, funct3) {
+                    (0, 0) => a32.wrapping_add(b32),
+                    (0x20, 0) => a32.wrapping_sub(b32),
+                    (0, 1) => a32 << (b32 & 0x1f),
+                    (0, 5) => a32 >> (b32 & 0x1f),
+                    (0x20, 5) => ((a32 as i32) >> (b32 & 0x1f)) as u32,
+                    (1, 0) => a32.wrapping_mul(b32),
+                    (1, 4) => match b32 as i32 {
+                        0 => u32::MAX,
+                        divisor => (a32 as i32).wrapping_div(divisor) as u32,
+                    },
+                    (1, 5) => a32.checked_div(b32).unwrap_or(u32::MAX),
+                    (1, 6) => match b32 as i32 {
+                        0 => a32,
+                        divisor => (a32 as i32).wrapping_rem(divisor) as u32,
+                    },
+                    (1, 7) => a32.checked_rem(b32).unwrap_or(a32),
+                    _ => return Err(illegal),
+                };
+                self.set(rd, value as i32 as i64 as u64);
+            }
+            // FENCE and FENCE.I, no-ops on a single hart
+            0x0f => {}
+            0x73 => match funct3 {
+                0 => match inst {
+                    0x0000_0073 => ecall = true,
+                    0x0010_0073 => return Err(Trap::Breakpoint { pc }),
+                    _ => return Err(illegal),
+                },
+                1..=3 | 5..=7 => {
+                    let csr = (inst >> 20) as u16;
+                    // Only reads of the counters are supported: CSRRS/CSRRC with x0 or a
+                    // zero immediate leave the CSR unchanged
+                    if !matches!(funct3, 2 | 3 | 6 | 7) || rs1 != 0 {
+                        return Err(Trap::UnsupportedCsr { pc, csr });
+                    }
+                    let value = match csr {
+                        CSR_CYCLE | CSR_INSTRET => self.instret,
+                        CSR_TIME => 0,
+                        _ => return Err(Trap::UnsupportedCsr { pc, csr }),
+                    };
+                    self.set(rd, value);
+                }
+                _ => return Err(illegal),
+            },
+            // Atomic memory operations
+            0x2f => {
+                let value = self.atomic(inst, funct3, a, b)?;
+                self.set(rd, value);
+            }
+            _ => return Err(illegal),
+        }
+
+        self.pc = next_pc;
+        Ok(ecall)
+    }
+
+    /// Execute an atomic memory operation, returning the value written to `rd`.
+    fn atomic(&mut self, inst: u32, funct3: u32, address: u64, b: u64) -> Result<u64, Trap> {
+        let illegal = Trap::IllegalInstruction {
+            pc: self.pc,
+            instruction: inst,
+        };
+        let len = match funct3 {
+            2 => 4,
+            3 => 8,
+            _ => return Err(illegal),
+        };
+        // Word-sized operations sign-extend the loaded word and use the low word of rs2
+        let (old, rhs) = if len == 8 {
+            (u64::from_le_bytes(self.load(address)?), b)
+        } else {
+            let old = i32::from_le_bytes(self.load(address)?) as i64 as u64;
+            (old, b as i32 as i64 as u64)
+        };
+        let new = match inst >> 27 {
+            // LR
+            0x02 => {
+                self.reservation = Some(address);
+                return Ok(old);
+            }
+            // SC
+            0x03 => {
+                if self.reservation.take() != Some(address) {
+                    return Ok(1);
+                }
+                self.store(address, &b.to_le_bytes()[..len])?;
+                return Ok(0);
+            }
+            0x01 => rhs,
+            0x00 => old.wrapping_add(rhs),
+            0x04 => old ^ rhs,
+            0x0c => old & rhs,
+            0x08 => old | rhs,
+            0x10 => (old as i64).min(rhs as i64) as u64,
+            0x14 => (old as i64).max(rhs as i64) as u64,
+            0x18 if len == 8 => old.min(rhs),
+            0x1c if len == 8 => old.max(rhs),
+            0x18 => (old as u32).min(rhs as u32) as u64,
+            0x1c => (old as u32).max(rhs as u32) as u64,
+            _ => return Err(illegal),
+        };
+        self.store(address, &new.to_le_bytes()[..len])?;
+        Ok(old)
+    }
+}
+
+/// Result of an M extension operation on 64-bit registers. Division by zero and overflow
+/// follow the specification instead of trapping.
+fn multiply_divide(funct3: u32, a: u64, b: u64) -> u64 {
+    match funct3 {
+        0 => a.wrapping_mul(b),
+        1 => ((a as i64 as i128 * b as i64 as i128) >> 64) as u64,
+        2 => ((a as i64 as i128 * b as u128 as i128) >> 64) as u64,
+        3 => ((a as u128 * b as u128) >> 64) as u64,
+        4 => match b as i64 {
+            0 => u64::MAX,
+            divisor => (a as i64).wrapping_div(divisor) as u64,
+        },
+        5 => a.checked_div(b).unwrap_or(u64::MAX),
+        6 => match b as i64 {
+            0 => a,
+            divisor => (a as i64).wrapping_rem(divisor) as u64,
+        },
+        _ => a.checked_rem(b).unwrap_or(a),
+    }
+}
+
+fn imm_s(inst: u32) -> u64 {
+    ((((inst as i32) >> 25) << 5) | ((inst >> 7) & 0x1f) as i32) as i64 as u64
+}
+
+fn imm_b(inst: u32) -> u64 {
+    let imm = (((inst as i32) >> 31) << 12)
+        | (((inst >> 7) & 0x1) << 11) as i32
+        | (((inst >> 25) & 0x3f) << 5) as i32
+        | (((inst >> 8) & 0xf) << 1) as i32;
+    imm as i64 as u64
+}
+
+fn imm_j(inst: u32) -> u64 {
+    let imm = (((inst as i32) >> 31) << 20)
+        | (inst & 0x000f_f000) as i32
+        | (((inst >> 20) & 0x1) << 11) as i32
+        | (((inst >> 21) & 0x3ff) << 1) as i32;
+    imm as i64 as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::asm::*;
+    use super::*;
+
+    fn run(program: &[u32]) -> (Cpu, Result<(), Trap>) {
+        let mut memory = Memory::default();
+        let code: Vec<u8> = program.iter().flat_map(|inst| inst.to_le_bytes()).collect();
+        memory.write(CODE_ADDR, &code).unwrap();
+        let mut cpu = Cpu::new(memory, CODE_ADDR);
+        let result = cpu.run_until_ecall(1000);
+        (cpu, result)
+    }
+
+    #[test]
+    fn executes_integer_and_multiply_divide_operations() {
+        let (cpu, result) = run(&[
+            addi(T0, ZERO, -7),
+            addi(T1, ZERO, 2),
+            add(A0, T0, T1),
+            sub(A1, T1, T0),
+            mul(A2, T0, T1),
+            div(T2, T0, T1),
+            mulhu(A7, T0, T1),
+            ECALL,
+        ]);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(cpu.regs[A0 as usize] as i64, -5);
+        assert_eq!(cpu.regs[A1 as usize], 9);
+        assert_eq!(cpu.regs[A2 as usize] as i64, -14);
+        // Signed division rounds towards zero
+        assert_eq!(cpu.regs[T2 as usize] as i64, -3);
+        assert_eq!(cpu.regs[A7 as usize], 1);
+        assert_eq!(cpu.pc, CODE_ADDR + 4 * 8);
+        assert_eq!(cpu.instret, 8);
+    }
+
+    #[test]
+    fn division_by_zero_and_word_overflow_follow_the_specification() {
+        let (cpu, result) = run(&[
+            addi(T0, ZERO, 5),
+            div(A0, T0, ZERO),
+            remu(A1, T0, ZERO),
+            // 0x7fffffff + 1 wraps to the sign-extended minimum word
+            lui(T1, 0x80000),
+            addi(T1, T1, -1),
+            addi(T2, ZERO, 1),
+            addw(A2, T1, T2),
+            ECALL,
+        ]);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(cpu.regs[A0 as usize], u64::MAX);
+        assert_eq!(cpu.regs[A1 as usize], 5);
+        assert_eq!(cpu.regs[A2 as usize], i32::MIN as i64 as u64);
+    }
+
+    #[test]
+    fn loops_over_memory_with_loads_stores_and_branches() {
+        // Store 1..=4 as doublewords, then sum them back
+        let data = 0x400;
+        let (cpu, result) = run(&[
+            addi(T1, ZERO, data),
+            addi(T0, ZERO, 4),
+            sd(T0, T1, 0),
+            addi(T1, T1, 8),
+            addi(T0, T0, -1),
+            bne(T0, ZERO, -12),
+            addi(T1, ZERO, data),
+            addi(T2, ZERO, 4),
+            ld(T0, T1, 0),
+            add(A0, A0, T0),
+            addi(T1, T1, 8),
+            addi(T2, T2, -1),
+            bne(T2, ZERO, -16),
+            lbu(A1, ZERO, data + 8),
+            lw(A2, ZERO, data + 16),
+            jal(ZERO, 8),
+            addi(A0, ZERO, 0),
+            ECALL,
+        ]);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(cpu.regs[A0 as usize], 10);
+        assert_eq!(cpu.regs[A1 as usize], 3);
+        assert_eq!(cpu.regs[A2 as usize], 2);
+        assert_eq!(
+            u64::from_le_bytes(cpu.memory.load(data as u64 + 24).unwrap()),
+            1
+        );
+    }
+
+    #[test]
+    fn executes_atomic_memory_operations() {
+        let (cpu, result) = run(&[
+            addi(T1, ZERO, 0x100),
+            addi(T0, ZERO, 40),
+            sd(T0, T1, 0),
+            addi(T2, ZERO, 2),
+            amoadd_d(A0, T2, T1),
+            lr_d(A1, T1),
+            sc_d(A2, T2, T1),
+            // The reservation was consumed by the successful store
+            sc_d(A7, T0, T1),
+            ECALL,
+        ]);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(cpu.regs[A0 as usize], 40);
+        assert_eq!(cpu.regs[A1 as usize], 42);
+        assert_eq!(cpu.regs[A2 as usize], 0);
+        assert_eq!(cpu.regs[A7 as usize], 1);
+        assert_eq!(u64::from_le_bytes(cpu.memory.load(0x100).unwrap()), 2);
+    }
+
+    #[test]
+    fn counts_retired_instructions_and_prints_to_the_console() {
+        let mut memory = Memory::default();
+        let program = [
+            addi(T0, ZERO, 0),
+            rdinstret(A0),
+            addi(T1, ZERO, b'o' as i32),
+            sb(T1, ZERO, 0x200),
+            sb(T1, ZERO, 0x200),
+            ECALL,
+        ];
+        let code: Vec<u8> = program.iter().flat_map(|inst| inst.to_le_bytes()).collect();
+        memory.write(CODE_ADDR, &code).unwrap();
+        let mut cpu = Cpu::new(memory, CODE_ADDR);
+        cpu.console_address = Some(0x200);
+
+        assert_eq!(cpu.run_until_ecall(1000), Ok(()));
+        assert_eq!(cpu.regs[A0 as usize], 1);
+        assert_eq!(cpu.console, b"oo");
+        assert_eq!(cpu.memory.read_u32(0x200).unwrap(), 0);
+    }
+
+    #[test]
+    fn stops_on_precompile_csrs_faults_and_the_instruction_limit() {
+        let (_, result) = run(&[csrrs(A0, 0x800, A1)]);
+        assert_eq!(
+            result,
+            Err(Trap::UnsupportedCsr {
+                pc: CODE_ADDR,
+                csr: 0x800
+            })
+        );
+
+        let (_, result) = run(&[lui(T0, 0x10000), slli(T0, T0, 8), ld(A0, T0, 0)]);
+        assert_eq!(
+            result,
+            Err(Trap::MemoryFault {
+                pc: CODE_ADDR + 8,
+                address: 1 << 36
+            })
+        );
+
+        let (cpu, result) = run(&[jal(ZERO, 0)]);
+        assert_eq!(result, Err(Trap::InstructionLimit { limit: 1000 }));
+        assert_eq!(cpu.instret, 1000);
+    }
+}
@@ -0,0 +1,104 @@
+use super::cpu::Memory;
+
+const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const EM_RISCV: u16 = 243;
+const PT_LOAD: u32 = 1;
+/// Size of an ELF64 program header.
+const PROGRAM_HEADER_SIZE: usize = 56;
+
+/// Load the `PT_LOAD` segments of a 64-bit little-endian RISC-V ELF into memory, returning
+/// the entry point.
+pub fn load_elf(elf: &[u8], memory: &mut Memory) -> Result<u64, String> {
+    if elf.get(..4) != Some(ELF_MAGIC) {
+        return Err("not an ELF file".to_string());
+    }
+    if elf.get(4) != Some(&ELFCLASS64) || elf.get(5) != Some(&ELFDATA2LSB) {
+        return Err("not a 64-bit little-endian ELF".to_string());
+    }
+    if read_u16(elf, 18)? != EM_RISCV {
+        return Err("not a RISC-V ELF".to_string());
+    }
+    let entry = read_u64(elf, 24)?;
+    let program_headers = read_u64(elf, 32)? as usize;
+    let header_size = read_u16(elf, 54)? as usize;
+    let header_count = read_u16(elf, 56)? as usize;
+    if header_size < PROGRAM_HEADER_SIZE {
+        return Err(format!(
+            "program headers of {header_size} bytes are too small"
+        ));
+    }
+
+    for i in 0..header_count {
+        let header = program_headers + i * header_size;
+        if read_u32(elf, header)? != PT_LOAD {
+            continue;
+        }
+        let offset = read_u64(elf, header + 8)? as usize;
+        let address = read_u64(elf, header + 16)?;
+        let file_size = read_u64(elf, header + 32)? as usize;
+        let memory_size = read_u64(elf, header + 40)?;
+        if (file_size as u64) > memory_size {
+            return Err(format!("segment {i} is larger in the file than in memory"));
+        }
+        let bytes = offset
+            .checked_add(file_size)
+            .and_then(|end| elf.get(offset..end))
+            .ok_or_else(|| format!("segment {i} extends past the end of the file"))?;
+        // The rest of the segment stays zero, as memory starts zeroed
+        memory
+            .write(address, bytes)
+            .map_err(|address| format!("segment {i} is loaded out of range at {address:#x}"))?;
+    }
+    Ok(entry)
+}
+
+fn field<const N: usize>(elf: &[u8], offset: usize) -> Result<[u8; N], String> {
+    offset
+        .checked_add(N)
+        .and_then(|end| elf.get(offset..end))
+        .map(|bytes| bytes.try_into().expect("slice has field length"))
+        .ok_or_else(|| format!("ELF truncated at offset {offset}"))
+}
+
+fn read_u16(elf: &[u8], offset: usize) -> Result<u16, String> {
+    field(elf, offset).map(u16::from_le_bytes)
+}
+
+fn read_u32(elf: &[u8], offset: usize) -> Result<u32, String> {
+    field(elf, offset).map(u32::from_le_bytes)
+}
+
+fn read_u64(elf: &[u8], offset: usize) -> Result<u64, String> {
+    field(elf, offset).map(u64::from_le_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::asm::{self, CODE_ADDR};
+    use super::*;
+
+    #[test]
+    fn loads_segments_at_their_address_and_returns_the_entry() {
+        let elf = asm::elf(&[asm::ECALL, 0x1234_5678]);
+        let mut memory = Memory::default();
+
+        assert_eq!(load_elf(&elf, &mut memory), Ok(CODE_ADDR));
+        assert_eq!(memory.read_u32(CODE_ADDR).unwrap(), asm::ECALL);
+        assert_eq!(memory.read_u32(CODE_ADDR + 4).unwrap(), 0x1234_5678);
+        assert_eq!(memory.read_u32(CODE_ADDR + 8).unwrap(), 0);
+    }
+
+    #[test]
+    fn rejects_other_files_and_truncated_segments() {
+        let mut memory = Memory::default();
+        let elf = asm::elf(&[asm::ECALL]);
+
+        let mut other_machine = elf.clone();
+        other_machine[18] = 62;
+        assert!(load_elf(&other_machine, &mut memory).is_err());
+        assert!(load_elf(b"#!/bin/sh", &mut memory).is_err());
+        assert!(load_elf(&elf[..elf.len() - 2], &mut memory).is_err());
+    }
+}
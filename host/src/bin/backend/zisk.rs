@@ -0,0 +1,152 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use ream_lib::{phase::PhaseSteps, preset::Preset, protocol::PublicOutputs};
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+use super::{ExecutionBackend, GuestOutput, Metrics};
+use crate::report::CaseStatus;
+
+/// Runs the guest ELF in the ZisK emulator, measuring the steps of every phase.
+pub struct ZiskBackend {
+    /// Directory of the guest crate, where `cargo-zisk` builds it.
+    pub guest_dir: PathBuf,
+    /// Guest ELF built by `cargo-zisk`.
+    pub guest_elf: PathBuf,
+    /// Directory the input files of the emulator are written to.
+    pub input_dir: PathBuf,
+}
+
+impl Default for ZiskBackend {
+    /// Paths relative to the workspace root, where the host is run from.
+    fn default() -> Self {
+        Self {
+            guest_dir: PathBuf::from("guest"),
+            guest_elf: PathBuf::from("target/riscv64ima-zisk-zkvm-elf/release/consenzisk_guest"),
+            input_dir: PathBuf::from("guest/build"),
+        }
+    }
+}
+
+impl ExecutionBackend for ZiskBackend {
+    fn name(&self) -> &'static str {
+        "zisk"
+    }
+
+    fn unit(&self) -> &'static str {
+        "steps"
+    }
+
+    fn build(&self, preset: Preset) -> Result<(), String> {
+        let mut build_args = vec!["build", "--release"];
+        if preset == Preset::Minimal {
+            build_args.extend(["--features", "minimal"]);
+        }
+        let status = Command::new("cargo-zisk")
+            .args(build_args)
+            .current_dir(&self.guest_dir)
+            .status()
+            .map_err(|e| format!("failed to run cargo-zisk: {e}"))?;
+        if !status.success() {
+            return Err("Guest code build failed!".to_string());
+        }
+        Ok(())
+    }
+
+    fn execute(&self, input_name: &str, input_bytes: &[u8]) -> Result<GuestOutput, CaseStatus> {
+        // Write input to its own file, so that concurrent runs do not overwrite each other
+        if !self.input_dir.exists() {
+            info!("Creating build directory at {:?}", self.input_dir);
+            fs::create_dir_all(&self.input_dir).map_err(|e| {
+                CaseStatus::EmulatorError(format!("failed to create build directory: {e}"))
+            })?;
+        }
+        let input_path = self.input_dir.join(format!("{input_name}.bin"));
+        fs::write(&input_path, input_bytes)
+            .map_err(|e| CaseStatus::EmulatorError(format!("failed to write input: {e}")))?;
+        info!("Input written to {:?}", input_path);
+
+        let output = Command::new("ziskemu")
+            .arg("-e")
+            .arg(&self.guest_elf)
+            .arg("-i")
+            .arg(&input_path)
+            .args(["-m", "-x"])
+            .output();
+        // The input is only needed while the emulator runs
+        if let Err(e) = fs::remove_file(&input_path) {
+            info!("Failed to remove input {:?}: {e}", input_path);
+        }
+        let output =
+            output.map_err(|e| CaseStatus::EmulatorError(format!("failed to run ZISK VM: {e}")))?;
+        if !output.status.success() {
+            return Err(failure_status(
+                &String::from_utf8_lossy(&output.stdout),
+                &String::from_utf8_lossy(&output.stderr),
+            ));
+        }
+
+        // Parse output from ZISK guest
+        Ok(read_output(
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+        ))
+    }
+
+    fn program_hash(&self) -> Option<String> {
+        file_sha256(&self.guest_elf)
+    }
+}
+
+/// Read the public outputs, phase markers and emulator metrics from the emulator output.
+pub fn read_output(stdout: String) -> GuestOutput {
+    let (steps, duration_secs) = parse_process_rom(&stdout).unzip();
+    GuestOutput {
+        public_outputs: PublicOutputs::parse(&stdout),
+        metrics: Metrics {
+            phase_costs: PhaseSteps::parse(&stdout),
+            steps,
+            duration_secs,
+            allocations: None,
+            allocated_bytes: None,
+        },
+        stdout,
+    }
+}
+
+/// Classify a failed emulator run. The guest prints its panic message to the ziskos UART,
+/// which the emulator forwards to its stdout, while the emulator reports its own errors on
+/// stderr.
+pub fn failure_status(stdout: &str, stderr: &str) -> CaseStatus {
+    if stdout.contains("panicked") || stderr.contains("panicked") {
+        CaseStatus::GuestPanic(format!("{stdout}{stderr}"))
+    } else {
+        CaseStatus::EmulatorError(stderr.to_string())
+    }
+}
+
+/// Steps and duration the emulator prints on its `process_rom() steps=... duration=...` line.
+fn parse_process_rom(stdout: &str) -> Option<(u64, f64)> {
+    let line = stdout
+        .lines()
+        .find(|line| line.contains("process_rom() steps="))?;
+    let value = |key: &str| {
+        line.split_whitespace()
+            .find_map(|token| token.strip_prefix(key))
+    };
+    Some((
+        value("steps=")?.parse().ok()?,
+        value("duration=")?.parse().ok()?,
+    ))
+}
+
+fn file_sha256(path: &Path) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    Some(
+        Sha256::digest(bytes)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect(),
+    )
+}
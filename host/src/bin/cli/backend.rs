@@ -0,0 +1,22 @@
+use clap::ValueEnum;
+use derive_more::Display;
+
+/// Where test case inputs are executed.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq, Display)]
+#[clap(rename_all = "lowercase")]
+pub enum Backend {
+    /// The guest ELF in the ZisK emulator, measured in steps.
+    #[default]
+    #[display("zisk")]
+    Zisk,
+
+    /// The same state transition run natively on the host, measured in wall-clock time and
+    /// allocations.
+    #[display("native")]
+    Native,
+
+    /// The guest ELF on a local rv64ima interpreter without zkVM precompiles, measured in
+    /// retired instructions.
+    #[display("riscv")]
+    Riscv,
+}
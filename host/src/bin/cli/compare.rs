@@ -0,0 +1,21 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Parser)]
+pub struct CompareArgs {
+    /// Results file of the baseline run, as written with `--results-file`.
+    pub baseline: PathBuf,
+
+    /// Results file of the run compared against the baseline.
+    pub current: PathBuf,
+
+    /// Step increase of a single test case, in percent, above which it is reported as a
+    /// regression.
+    #[clap(long, default_value_t = 5.0)]
+    pub case_threshold: f64,
+
+    /// Step increase of an operation's summed test cases, in percent, above which it is
+    /// reported as a regression.
+    #[clap(long, default_value_t = 2.0)]
+    pub operation_threshold: f64,
+}
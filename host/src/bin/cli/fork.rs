@@ -1,5 +1,6 @@
 use clap::{Parser, ValueEnum};
 use derive_more::Display;
+use ream_lib::fork::ForkType;
 
 #[derive(Debug, Clone, Parser)]
 pub struct ForkArgs {
@@ -17,3 +18,13 @@ pub enum Fork {
     #[display("fulu")]
     Fulu,
 }
+
+// Convert Fork to ForkType using From trait
+impl From<Fork> for ForkType {
+    fn from(fork: Fork) -> Self {
+        match fork {
+            Fork::Electra => ForkType::Electra,
+            Fork::Fulu => ForkType::Fulu,
+        }
+    }
+}
@@ -1,2 +1,6 @@
+pub mod backend;
+pub mod compare;
 pub mod fork;
 pub mod operation;
+pub mod preset;
+pub mod spec_tests;
@@ -2,12 +2,16 @@ use clap::{Parser, Subcommand, ValueEnum};
 use derive_more::Display;
 use ream_lib::{
     file::ssz_from_file,
+    fork::ForkType,
     input::{
-        BlockOperationType, BlockOperationWrapper, EpochOperationType, EpochOperationWrapper,
-        OperationInput,
+        BlockOperationType, BlockOperationWrapper, BlocksWrapper, EpochOperationType,
+        EpochOperationWrapper, ForkUpgradeWrapper, OperationInput, RewardsWrapper, SlotsWrapper,
     },
+    state::ForkedBeaconState,
 };
-use std::path::PathBuf;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::path::{Path, PathBuf};
+use tree_hash::Hash256;
 #[derive(Debug, Clone, Parser)]
 pub struct OperationArgs {
     #[clap(subcommand)]
@@ -24,6 +28,24 @@ pub enum Operation {
         #[clap(value_enum)]
         operation: EpochOperation,
     },
+    Sanity {
+        #[clap(value_enum)]
+        operation: SanityOperation,
+    },
+    Finality {
+        #[clap(value_enum)]
+        operation: FinalityOperation,
+    },
+    Rewards {
+        #[clap(value_enum)]
+        operation: RewardsOperation,
+    },
+    Fork {
+        #[clap(value_enum)]
+        operation: ForkOperation,
+    },
+    /// Compare the step counts of two results files instead of running test cases.
+    Compare(crate::cli::compare::CompareArgs),
 }
 
 #[derive(ValueEnum, Debug, Clone, Display)]
@@ -80,30 +102,147 @@ pub enum EpochOperation {
     HistoricalSummariesUpdate,
     #[display("participation_flag_updates")]
     ParticipationFlagUpdates,
+    #[display("sync_committee_updates")]
+    SyncCommitteeUpdates,
+    #[display("proposer_lookahead")]
+    ProposerLookahead,
+    #[display("full")]
+    FullEpoch,
+}
+
+#[derive(ValueEnum, Debug, Clone, Display)]
+#[clap(rename_all = "snake_case")]
+pub enum SanityOperation {
+    #[display("blocks")]
+    Blocks,
+    #[display("slots")]
+    Slots,
+}
+
+#[derive(ValueEnum, Debug, Clone, Display)]
+#[clap(rename_all = "snake_case")]
+pub enum FinalityOperation {
+    #[display("finality")]
+    Finality,
+}
+
+#[derive(ValueEnum, Debug, Clone, Display)]
+#[clap(rename_all = "snake_case")]
+pub enum RewardsOperation {
+    #[display("basic")]
+    Basic,
+    #[display("leak")]
+    Leak,
+    #[display("random")]
+    Random,
+}
+
+#[derive(ValueEnum, Debug, Clone, Display)]
+#[clap(rename_all = "snake_case")]
+pub enum ForkOperation {
+    #[display("upgrade")]
+    Upgrade,
+}
+
+/// Contents of `execution.yaml` in execution_payload test cases.
+#[derive(Debug, Deserialize)]
+struct ExecutionMeta {
+    execution_valid: bool,
+}
+
+/// Contents of `meta.yaml` in test cases that import a sequence of blocks.
+#[derive(Debug, Deserialize)]
+struct BlocksMeta {
+    blocks_count: usize,
 }
 
 // Generic traits for operation handling
 pub trait OperationHandler: std::fmt::Display {
-    fn prepare_input(&self, case_dir: &PathBuf) -> ream_lib::input::OperationInput;
-    fn load_test_cases(&self, fork: &crate::cli::fork::Fork) -> (PathBuf, Vec<String>);
+    /// Read the operation input from the test case files, failing on missing or invalid files.
+    fn prepare_input(&self, case_dir: &Path) -> Result<OperationInput, String>;
+    /// Locate the test cases of the operation under the directory of the preset's vectors.
+    fn load_test_cases(
+        &self,
+        test_case_dir: &Path,
+        fork: &crate::cli::fork::Fork,
+    ) -> (PathBuf, Vec<String>);
     fn get_operation_category(&self) -> &'static str;
+
+    /// Inputs for the individual steps this operation is made of at the fork. Their summed
+    /// cost is reported next to the cost of the operation itself.
+    fn sub_operations(&self, _fork: ForkType) -> Vec<OperationInput> {
+        Vec::new()
+    }
+
+    /// The SSZ-encoded pre-state passed to the guest next to the operation input.
+    fn load_pre_state(&self, case_dir: &Path) -> Result<Vec<u8>, String> {
+        ssz_from_file(&case_dir.join("pre.ssz_snappy"))
+    }
+
+    /// Root of the pre-state the guest is expected to commit for the test case.
+    fn pre_state_root(
+        &self,
+        fork: ForkType,
+        pre_state_ssz_bytes: &[u8],
+        _input: &OperationInput,
+    ) -> Result<Hash256, String> {
+        ForkedBeaconState::from_ssz_bytes(fork, pre_state_ssz_bytes)
+            .map(|state| state.tree_hash_root())
+            .map_err(|e| format!("invalid pre-state: {e:?}"))
+    }
+
+    /// Whether the `post.ssz_snappy` of a test case is the expected result of this operation.
+    fn has_spec_post_state(&self) -> bool {
+        true
+    }
+
+    /// Whether the operation has no expected result in the specs and is instead required to
+    /// succeed and to match the host's recomputed post state, even without `--compare-recompute`.
+    fn checked_by_recompute(&self) -> bool {
+        false
+    }
+
+    /// Whether a rejected input leaves the pre-state untouched. A state transition runs
+    /// its blocks on the state in place, so the blocks before the failing one are applied.
+    fn rejection_keeps_pre_state(&self) -> bool {
+        true
+    }
+
+    /// Whether the finalized checkpoint reported by the guest is checked against the specs.
+    fn checks_finalized_checkpoint(&self) -> bool {
+        false
+    }
+
+    /// Whether the deltas digest reported by the guest is checked against the specs.
+    fn checks_deltas(&self) -> bool {
+        false
+    }
 }
 
 // Block operation trait implementation
 impl OperationHandler for BlockOperation {
-    fn prepare_input(&self, case_dir: &PathBuf) -> ream_lib::input::OperationInput {
+    fn prepare_input(&self, case_dir: &Path) -> Result<OperationInput, String> {
         let input_path = case_dir.join(format!("{}.ssz_snappy", self.get_input_filename()));
-        let ssz_bytes = ssz_from_file(&input_path);
+        let ssz_bytes = ssz_from_file(&input_path)?;
+        let execution_valid = match self {
+            BlockOperation::ExecutionPayload => {
+                yaml_from_file::<ExecutionMeta>(&case_dir.join("execution.yaml"))?.execution_valid
+            }
+            _ => true,
+        };
 
-        OperationInput::Block(BlockOperationWrapper {
+        Ok(OperationInput::Block(BlockOperationWrapper {
             operation_type: BlockOperationType::from(self.clone()),
             ssz_bytes,
-        })
+            execution_valid,
+        }))
     }
 
-    fn load_test_cases(&self, fork: &crate::cli::fork::Fork) -> (PathBuf, Vec<String>) {
-        let test_case_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("mainnet");
-
+    fn load_test_cases(
+        &self,
+        test_case_dir: &Path,
+        fork: &crate::cli::fork::Fork,
+    ) -> (PathBuf, Vec<String>) {
         let base_dir = test_case_dir
             .join(format!("{}", fork))
             .join(self.get_operation_category())
@@ -121,15 +260,102 @@ impl OperationHandler for BlockOperation {
 
 // Epoch operation trait implementation
 impl OperationHandler for EpochOperation {
-    fn prepare_input(&self, _case_dir: &PathBuf) -> ream_lib::input::OperationInput {
+    fn prepare_input(&self, _case_dir: &Path) -> Result<OperationInput, String> {
+        Ok(self.input())
+    }
+
+    fn load_test_cases(
+        &self,
+        test_case_dir: &Path,
+        fork: &crate::cli::fork::Fork,
+    ) -> (PathBuf, Vec<String>) {
+        let category_dir = test_case_dir
+            .join(format!("{}", fork))
+            .join(self.get_operation_category());
+
+        if let EpochOperation::FullEpoch = self {
+            // Every epoch_processing pre-state is an input for the full epoch transition
+            let test_cases = EpochOperation::sub_steps(ForkType::from(fork.clone()))
+                .flat_map(|step| {
+                    let step_dir = category_dir.join(step.to_string()).join("pyspec_tests");
+                    ream_lib::file::get_test_cases(&step_dir)
+                        .into_iter()
+                        .map(move |test_case| format!("{step}/pyspec_tests/{test_case}"))
+                })
+                .collect();
+            return (category_dir, test_cases);
+        }
+
+        let base_dir = category_dir.join(self.to_string()).join("pyspec_tests");
+
+        let test_cases = ream_lib::file::get_test_cases(&base_dir);
+        (base_dir, test_cases)
+    }
+
+    fn get_operation_category(&self) -> &'static str {
+        "epoch_processing"
+    }
+
+    fn sub_operations(&self, fork: ForkType) -> Vec<OperationInput> {
+        match self {
+            EpochOperation::FullEpoch => EpochOperation::sub_steps(fork)
+                .map(|step| step.input())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn has_spec_post_state(&self) -> bool {
+        // The post states of the epoch_processing vectors only cover a single step
+        !matches!(self, EpochOperation::FullEpoch)
+    }
+
+    fn checked_by_recompute(&self) -> bool {
+        matches!(self, EpochOperation::FullEpoch)
+    }
+}
+
+// Epoch operation specific methods
+impl EpochOperation {
+    /// Epoch processing inputs carry no data, as the step runs on the pre-state alone.
+    fn input(&self) -> OperationInput {
         OperationInput::Epoch(EpochOperationWrapper {
             operation_type: EpochOperationType::from(self.clone()),
         })
     }
 
-    fn load_test_cases(&self, fork: &crate::cli::fork::Fork) -> (PathBuf, Vec<String>) {
-        let test_case_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("mainnet");
+    /// The individual epoch processing steps, in the order `process_epoch` runs them at
+    /// the fork.
+    fn sub_steps(fork: ForkType) -> impl Iterator<Item = EpochOperation> {
+        EpochOperation::value_variants()
+            .iter()
+            .filter(move |step| match step {
+                EpochOperation::FullEpoch => false,
+                EpochOperation::ProposerLookahead => fork == ForkType::Fulu,
+                _ => true,
+            })
+            .cloned()
+    }
+}
+
+// Sanity operation trait implementation
+impl OperationHandler for SanityOperation {
+    fn prepare_input(&self, case_dir: &Path) -> Result<OperationInput, String> {
+        match self {
+            SanityOperation::Blocks => prepare_blocks_input(case_dir),
+            SanityOperation::Slots => {
+                let slots: u64 = yaml_from_file(&case_dir.join("slots.yaml"))?;
 
+                Ok(OperationInput::Slots(SlotsWrapper { slots }))
+            }
+        }
+    }
+
+    fn load_test_cases(
+        &self,
+        test_case_dir: &Path,
+        fork: &crate::cli::fork::Fork,
+    ) -> (PathBuf, Vec<String>) {
         let base_dir = test_case_dir
             .join(format!("{}", fork))
             .join(self.get_operation_category())
@@ -141,8 +367,151 @@ impl OperationHandler for EpochOperation {
     }
 
     fn get_operation_category(&self) -> &'static str {
-        "epoch_processing"
+        "sanity"
+    }
+
+    fn rejection_keeps_pre_state(&self) -> bool {
+        !matches!(self, SanityOperation::Blocks)
+    }
+}
+
+// Finality operation trait implementation
+impl OperationHandler for FinalityOperation {
+    fn prepare_input(&self, case_dir: &Path) -> Result<OperationInput, String> {
+        match self {
+            FinalityOperation::Finality => prepare_blocks_input(case_dir),
+        }
+    }
+
+    fn load_test_cases(
+        &self,
+        test_case_dir: &Path,
+        fork: &crate::cli::fork::Fork,
+    ) -> (PathBuf, Vec<String>) {
+        let base_dir = test_case_dir
+            .join(format!("{}", fork))
+            .join(self.get_operation_category())
+            .join(self.to_string())
+            .join("pyspec_tests");
+
+        let test_cases = ream_lib::file::get_test_cases(&base_dir);
+        (base_dir, test_cases)
+    }
+
+    fn get_operation_category(&self) -> &'static str {
+        "finality"
     }
+
+    fn rejection_keeps_pre_state(&self) -> bool {
+        false
+    }
+
+    fn checks_finalized_checkpoint(&self) -> bool {
+        true
+    }
+}
+
+// Rewards operation trait implementation
+impl OperationHandler for RewardsOperation {
+    fn prepare_input(&self, _case_dir: &Path) -> Result<OperationInput, String> {
+        Ok(OperationInput::Rewards(RewardsWrapper {}))
+    }
+
+    fn load_test_cases(
+        &self,
+        test_case_dir: &Path,
+        fork: &crate::cli::fork::Fork,
+    ) -> (PathBuf, Vec<String>) {
+        let base_dir = test_case_dir
+            .join(format!("{}", fork))
+            .join(self.get_operation_category())
+            .join(self.to_string())
+            .join("pyspec_tests");
+
+        let test_cases = ream_lib::file::get_test_cases(&base_dir);
+        (base_dir, test_cases)
+    }
+
+    fn get_operation_category(&self) -> &'static str {
+        "rewards"
+    }
+
+    fn has_spec_post_state(&self) -> bool {
+        // Rewards vectors provide the expected deltas instead of a post state
+        false
+    }
+
+    fn checks_deltas(&self) -> bool {
+        true
+    }
+}
+
+// Fork operation trait implementation
+impl OperationHandler for ForkOperation {
+    fn prepare_input(&self, case_dir: &Path) -> Result<OperationInput, String> {
+        match self {
+            ForkOperation::Upgrade => Ok(OperationInput::ForkUpgrade(ForkUpgradeWrapper {
+                pre_state_ssz_bytes: ssz_from_file(&case_dir.join("pre.ssz_snappy"))?,
+            })),
+        }
+    }
+
+    fn load_test_cases(
+        &self,
+        test_case_dir: &Path,
+        fork: &crate::cli::fork::Fork,
+    ) -> (PathBuf, Vec<String>) {
+        // Upgrade vectors live under `fork/fork`, named after the handler rather than the operation
+        let base_dir = test_case_dir
+            .join(format!("{}", fork))
+            .join(self.get_operation_category())
+            .join("fork")
+            .join("pyspec_tests");
+
+        let test_cases = ream_lib::file::get_test_cases(&base_dir);
+        (base_dir, test_cases)
+    }
+
+    fn get_operation_category(&self) -> &'static str {
+        "fork"
+    }
+
+    fn load_pre_state(&self, _case_dir: &Path) -> Result<Vec<u8>, String> {
+        // The pre-state belongs to the previous fork and is carried by the operation input
+        Ok(Vec::new())
+    }
+
+    fn pre_state_root(
+        &self,
+        fork: ForkType,
+        _pre_state_ssz_bytes: &[u8],
+        input: &OperationInput,
+    ) -> Result<Hash256, String> {
+        let OperationInput::ForkUpgrade(wrapper) = input else {
+            return Err("expected a fork upgrade input".to_string());
+        };
+        wrapper
+            .decode_pre_state(fork)
+            .map(|pre_state| pre_state.tree_hash_root())
+            .map_err(|e| format!("invalid pre-state: {e}"))
+    }
+}
+
+/// Load the `blocks_<i>.ssz_snappy` signed blocks listed by the test case `meta.yaml`.
+fn prepare_blocks_input(case_dir: &Path) -> Result<OperationInput, String> {
+    let meta: BlocksMeta = yaml_from_file(&case_dir.join("meta.yaml"))?;
+    let blocks = (0..meta.blocks_count)
+        .map(|i| ssz_from_file(&case_dir.join(format!("blocks_{i}.ssz_snappy"))))
+        .collect::<Result<_, _>>()?;
+
+    Ok(OperationInput::Blocks(BlocksWrapper { blocks }))
+}
+
+fn yaml_from_file<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let raw = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read file: {:?}: {}", path, e))?;
+
+    serde_yaml::from_str(&raw).map_err(|e| format!("Could not parse yaml {:?}: {}", path, e))
 }
 
 // Block operation specific methods
@@ -204,6 +573,9 @@ impl From<EpochOperation> for EpochOperationType {
             EpochOperation::ParticipationFlagUpdates => {
                 EpochOperationType::ParticipationFlagUpdates
             }
+            EpochOperation::SyncCommitteeUpdates => EpochOperationType::SyncCommitteeUpdates,
+            EpochOperation::ProposerLookahead => EpochOperationType::ProposerLookahead,
+            EpochOperation::FullEpoch => EpochOperationType::FullEpoch,
         }
     }
 }
@@ -0,0 +1,30 @@
+use clap::{Parser, ValueEnum};
+use derive_more::Display;
+use ream_lib::preset::Preset;
+
+#[derive(Debug, Clone, Parser)]
+pub struct PresetArgs {
+    #[clap(long, default_value_t = PresetName::Mainnet)]
+    pub preset: PresetName,
+}
+
+#[derive(ValueEnum, Debug, Clone, Default, Display)]
+#[clap(rename_all = "lowercase")]
+pub enum PresetName {
+    #[default]
+    #[display("mainnet")]
+    Mainnet,
+
+    #[display("minimal")]
+    Minimal,
+}
+
+// Convert PresetName to Preset using From trait
+impl From<PresetName> for Preset {
+    fn from(preset: PresetName) -> Self {
+        match preset {
+            PresetName::Mainnet => Preset::Mainnet,
+            PresetName::Minimal => Preset::Minimal,
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use clap::Parser;
+use ream_lib::preset::Preset;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Parser)]
+pub struct SpecTestsArgs {
+    /// Directory holding the preset directories of the spec tests, e.g. the `tests` directory
+    /// of an extracted consensus-spec-tests release.
+    #[clap(long, env = "SPEC_TESTS_DIR", default_value = env!("CARGO_MANIFEST_DIR"))]
+    pub spec_tests_dir: PathBuf,
+
+    /// Release of the spec tests, for a directory holding several releases side by side as
+    /// `<spec_tests_dir>/<version>`.
+    #[clap(long, env = "SPEC_TESTS_VERSION")]
+    pub spec_tests_version: Option<String>,
+}
+
+impl SpecTestsArgs {
+    /// Directory of the preset's vectors, holding one directory per fork.
+    pub fn test_case_dir(&self, preset: Preset) -> PathBuf {
+        let mut dir = self.spec_tests_dir.clone();
+        if let Some(version) = &self.spec_tests_version {
+            dir.push(version);
+        }
+        dir.join(preset.name())
+    }
+}
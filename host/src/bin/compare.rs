@@ -0,0 +1,252 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+
+use tracing::info;
+
+use crate::cli::compare::CompareArgs;
+use crate::report::CaseStatus;
+use crate::results::{read_results, CaseResult};
+
+/// Steps of the same test case or operation in the baseline and current runs.
+#[derive(Debug, Clone, Copy, Default)]
+struct StepDelta {
+    baseline: u64,
+    current: u64,
+}
+
+impl StepDelta {
+    /// Change from the baseline, in percent.
+    fn percent(&self) -> f64 {
+        (self.current as f64 - self.baseline as f64) / self.baseline.max(1) as f64 * 100.0
+    }
+}
+
+/// Compare the steps of two results files, returning whether no test case or operation
+/// regressed beyond its threshold.
+pub fn compare_results(args: &CompareArgs) -> Result<bool, Box<dyn Error>> {
+    let baseline = read_results(&args.baseline)?;
+    let current = read_results(&args.current)?;
+    compare_runs(&baseline, &current, args)
+}
+
+/// Compare the steps of the passed test cases of two runs of the same backend, fork and
+/// preset. A test case that passed in the baseline but is missing or failing in the current
+/// run counts as a regression, and runs without a passed test case in common are an error.
+fn compare_runs(
+    baseline: &[CaseResult],
+    current: &[CaseResult],
+    args: &CompareArgs,
+) -> Result<bool, Box<dyn Error>> {
+    let baseline_run = run_of(baseline)?;
+    let current_run = run_of(current)?;
+    if baseline_run != current_run {
+        return Err(format!(
+            "the baseline ran {} but the current run {}",
+            describe_run(baseline_run),
+            describe_run(current_run)
+        )
+        .into());
+    }
+    let baseline = steps_by_case(baseline);
+    let current = steps_by_case(current);
+    if !baseline.keys().any(|case| current.contains_key(case)) {
+        return Err("the runs have no passed test case with steps in common".into());
+    }
+    let mut regressed = false;
+
+    info!("Per-case steps: operation | test_case | baseline | current | delta");
+    let mut operation_deltas: BTreeMap<&str, StepDelta> = BTreeMap::new();
+    for ((operation, test_case), &baseline_steps) in &baseline {
+        let Some(&current_steps) = current.get(&(operation.clone(), *test_case)) else {
+            regressed = true;
+            info!("{operation} | {test_case} | {baseline_steps} | missing or failing | REGRESSION");
+            continue;
+        };
+        let delta = StepDelta {
+            baseline: baseline_steps,
+            current: current_steps,
+        };
+        let operation_delta = operation_deltas.entry(operation).or_default();
+        operation_delta.baseline += delta.baseline;
+        operation_delta.current += delta.current;
+
+        let regression = delta.percent() > args.case_threshold;
+        regressed |= regression;
+        info!(
+            "{operation} | {test_case} | {baseline_steps} | {current_steps} | {:+.2}%{}",
+            delta.percent(),
+            if regression { " REGRESSION" } else { "" }
+        );
+    }
+    for ((operation, test_case), current_steps) in &current {
+        if !baseline.contains_key(&(operation.clone(), *test_case)) {
+            info!("{operation} | {test_case} | missing | {current_steps} | -");
+        }
+    }
+
+    info!("Per-operation steps of the common test cases: operation | baseline | current | delta");
+    for (operation, delta) in &operation_deltas {
+        let regression = delta.percent() > args.operation_threshold;
+        regressed |= regression;
+        info!(
+            "{operation} | {} | {} | {:+.2}%{}",
+            delta.baseline,
+            delta.current,
+            delta.percent(),
+            if regression { " REGRESSION" } else { "" }
+        );
+    }
+
+    if regressed {
+        info!(
+            "Steps regressed beyond the thresholds of {}% per test case or {}% per operation, \
+             or a test case passed in the baseline is missing or failing",
+            args.case_threshold, args.operation_threshold
+        );
+    }
+    Ok(!regressed)
+}
+
+/// Backend, fork and preset of a run.
+type Run<'a> = (&'a str, &'a str, &'a str);
+
+fn describe_run((backend, fork, preset): Run) -> String {
+    format!("{backend} on {fork} with the {preset} preset")
+}
+
+/// The backend, fork and preset shared by every record of a results file.
+fn run_of(results: &[CaseResult]) -> Result<Run<'_>, String> {
+    let runs: BTreeSet<Run> = results
+        .iter()
+        .map(|result| {
+            (
+                result.backend.as_str(),
+                result.fork.as_str(),
+                result.preset.as_str(),
+            )
+        })
+        .collect();
+    match runs.len() {
+        0 => Err("a results file holds no test case".to_string()),
+        1 => Ok(*runs.first().expect("one run")),
+        _ => Err(format!(
+            "a results file mixes runs of {}",
+            runs.into_iter()
+                .map(describe_run)
+                .collect::<Vec<_>>()
+                .join(" and ")
+        )),
+    }
+}
+
+/// Total steps of every passed test case that reported them, keyed by
+/// `<category>/<operation>` and test case.
+fn steps_by_case(results: &[CaseResult]) -> BTreeMap<(String, &str), u64> {
+    results
+        .iter()
+        .filter(|result| result.status == CaseStatus::Passed.label())
+        .filter_map(|result| {
+            let operation = format!("{}/{}", result.category, result.operation);
+            Some(((operation, result.test_case.as_str()), result.steps?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn args() -> CompareArgs {
+        CompareArgs {
+            baseline: PathBuf::new(),
+            current: PathBuf::new(),
+            case_threshold: 5.0,
+            operation_threshold: 2.0,
+        }
+    }
+
+    fn case(test_case: &str, status: &CaseStatus, steps: Option<u64>) -> CaseResult {
+        CaseResult {
+            backend: "zisk".to_string(),
+            fork: "electra".to_string(),
+            preset: "mainnet".to_string(),
+            category: "block".to_string(),
+            operation: "attestation".to_string(),
+            test_case: test_case.to_string(),
+            status: status.label().to_string(),
+            outcome: None,
+            steps,
+            duration_secs: None,
+            phase_steps: BTreeMap::new(),
+            phase_allocations: BTreeMap::new(),
+            allocations: None,
+            allocated_bytes: None,
+            pre_state_bytes: 0,
+            operation_bytes: 0,
+            guest_elf_sha256: None,
+            git_revision: None,
+        }
+    }
+
+    fn passed(test_case: &str, steps: u64) -> CaseResult {
+        case(test_case, &CaseStatus::Passed, Some(steps))
+    }
+
+    #[test]
+    fn steps_within_the_thresholds_pass() {
+        let baseline = [passed("a", 100), passed("b", 100)];
+        let current = [passed("a", 104), passed("b", 98), passed("c", 500)];
+
+        assert!(compare_runs(&baseline, &current, &args()).unwrap());
+    }
+
+    #[test]
+    fn steps_beyond_a_threshold_regress() {
+        let baseline = [passed("a", 100), passed("b", 100)];
+
+        let case_regression = [passed("a", 106), passed("b", 90)];
+        assert!(!compare_runs(&baseline, &case_regression, &args()).unwrap());
+
+        let operation_regression = [passed("a", 104), passed("b", 104)];
+        assert!(!compare_runs(&baseline, &operation_regression, &args()).unwrap());
+    }
+
+    #[test]
+    fn a_baseline_case_missing_or_failing_in_the_current_run_regresses() {
+        let baseline = [passed("a", 100), passed("b", 100)];
+
+        let missing = [passed("a", 100)];
+        assert!(!compare_runs(&baseline, &missing, &args()).unwrap());
+
+        let failing = [
+            passed("a", 100),
+            case("b", &CaseStatus::RootMismatch(String::new()), Some(100)),
+        ];
+        assert!(!compare_runs(&baseline, &failing, &args()).unwrap());
+
+        let without_steps = [passed("a", 100), case("b", &CaseStatus::Passed, None)];
+        assert!(!compare_runs(&baseline, &without_steps, &args()).unwrap());
+    }
+
+    #[test]
+    fn runs_of_another_backend_fork_or_preset_are_not_compared() {
+        let baseline = [passed("a", 100)];
+        let mut current = passed("a", 100);
+        current.fork = "fulu".to_string();
+
+        assert!(compare_runs(&baseline, &[current], &args()).is_err());
+    }
+
+    #[test]
+    fn runs_without_passed_cases_in_common_are_not_compared() {
+        let baseline = [passed("a", 100)];
+
+        assert!(compare_runs(&baseline, &[passed("b", 100)], &args()).is_err());
+        assert!(compare_runs(&baseline, &[], &args()).is_err());
+        // Native results report no steps
+        let native = [case("a", &CaseStatus::Passed, None)];
+        assert!(compare_runs(&native, &native, &args()).is_err());
+    }
+}
@@ -1,17 +1,38 @@
 use clap::Parser;
-use ream_lib::{file::ssz_from_file, input::OperationInput, ssz::from_ssz_bytes};
-use std::path::PathBuf;
+use ream_lib::{
+    checkpoint::FinalizedCheckpoint,
+    file::ssz_from_file,
+    fork::ForkType,
+    input::OperationInput,
+    outcome::Outcome,
+    phase::{Phase, PhaseSteps},
+    preset::Preset,
+    protocol::{PublicOutputs, ZiskInput},
+    rewards::{deltas_digest, DELTAS_FILES},
+    state::ForkedBeaconState,
+};
+use std::path::{Path, PathBuf};
 use tracing::info;
-use tree_hash::{Hash256, TreeHash};
+use tree_hash::Hash256;
+mod backend;
 mod cli;
+mod compare;
+mod report;
+mod results;
+use backend::{ExecutionBackend, GuestOutput};
 use cli::{
+    backend::Backend,
     fork::Fork,
     operation::{Operation, OperationHandler},
 };
-use ream_consensus::electra::beacon_state::BeaconState;
-use serde::{Deserialize, Serialize};
-use std::fs;
-use std::process::Command;
+use report::{check_eq, log_case_summary, CaseStatus};
+use results::{write_results, CaseResult, RunInfo};
+use std::collections::BTreeMap;
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    mpsc,
+};
+use std::thread;
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
@@ -19,123 +40,466 @@ struct Args {
     fork: cli::fork::ForkArgs,
     #[clap(flatten)]
     operation: cli::operation::OperationArgs,
+    #[clap(flatten)]
+    preset: cli::preset::PresetArgs,
+    #[clap(flatten)]
+    spec_tests: cli::spec_tests::SpecTestsArgs,
     #[clap(long, default_value_t = true)]
     compare_specs: bool,
     #[clap(long, default_value_t = false)]
     compare_recompute: bool,
     #[clap(long)]
     excluded_cases: Vec<String>,
+    /// Stop at the first failing test case instead of running the remaining ones.
+    #[clap(long, default_value_t = false)]
+    fail_fast: bool,
+    /// Number of test cases executed concurrently.
+    #[clap(long, short, default_value_t = 1)]
+    jobs: usize,
+    /// File the per-case benchmark results are written to, as CSV for a `.csv` extension
+    /// and as JSON otherwise.
+    #[clap(long)]
+    results_file: Option<PathBuf>,
+    /// Where the test case inputs are executed.
+    #[clap(long, value_enum, default_value_t = Backend::Zisk)]
+    backend: Backend,
 }
-#[derive(Serialize, Deserialize)]
-pub struct ZiskInput {
-    pre_state_ssz_bytes: Vec<u8>,
-    operation_input: Vec<u8>, // or your specific operation type
+
+/// Options shared by every test case of a run.
+struct RunOptions {
+    /// Directory of the spec test vectors, holding one directory per fork.
+    test_case_dir: PathBuf,
+    preset: Preset,
+    excluded_cases: Vec<String>,
+    compare_specs: bool,
+    compare_recompute: bool,
+    fail_fast: bool,
+    jobs: usize,
+    results_file: Option<PathBuf>,
+    backend: Box<dyn ExecutionBackend>,
 }
 
 fn main() {
     setup_log();
-    let (fork, operation, excluded_cases, compare_specs, compare_recompute) = parse_args();
-    let build_guest_result = Command::new("cargo-zisk")
-        .args(["build", "--release"])
-        .current_dir("guest")
-        .status()
-        .expect("Failed to build guest code");
-    if !build_guest_result.success() {
-        eprintln!("Guest code build failed!");
+    let (fork, preset, operation, options) = parse_args();
+    if let Operation::Compare(compare_args) = &operation {
+        match compare::compare_results(compare_args) {
+            Ok(true) => return,
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("Failed to compare results: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+    if preset != Preset::COMPILED {
+        // The host decodes the same states as the guest, so both must share the preset
+        let hint = match preset {
+            Preset::Minimal => "with `--features minimal`",
+            Preset::Mainnet => "without the `minimal` feature",
+        };
+        eprintln!(
+            "The host is built for the {} preset, but {preset} was requested. Rebuild it {hint}.",
+            Preset::COMPILED
+        );
+        std::process::exit(1);
+    }
+    if let Err(e) = options.backend.build(preset) {
+        eprintln!("{e}");
         std::process::exit(1);
     }
-    match operation {
+    let summary = match operation {
         Operation::Block {
             operation: block_op,
-        } => {
-            run_tests(
-                &fork,
-                &block_op,
-                excluded_cases,
-                compare_specs,
-                compare_recompute,
-            );
-        }
+        } => run_tests(&fork, &block_op, &options),
         Operation::Epoch {
             operation: epoch_op,
-        } => {
-            run_tests(
-                &fork,
-                &epoch_op,
-                excluded_cases,
-                compare_specs,
-                compare_recompute,
-            );
-        }
+        } => run_tests(&fork, &epoch_op, &options),
+        Operation::Sanity {
+            operation: sanity_op,
+        } => run_tests(&fork, &sanity_op, &options),
+        Operation::Finality {
+            operation: finality_op,
+        } => run_tests(&fork, &finality_op, &options),
+        Operation::Rewards {
+            operation: rewards_op,
+        } => run_tests(&fork, &rewards_op, &options),
+        Operation::Fork { operation: fork_op } => run_tests(&fork, &fork_op, &options),
+        Operation::Compare(_) => unreachable!("results are compared before the guest is built"),
+    };
+    if !summary.passed() {
+        std::process::exit(1);
+    }
+}
+
+/// Guest runs of a test case, executed on a worker thread.
+struct CaseRun {
+    input: OperationInput,
+    pre_state_ssz_bytes: Vec<u8>,
+    /// Output of the operation run, followed by the outputs of its sub-operation runs.
+    outputs: Result<(GuestOutput, Vec<GuestOutput>), CaseStatus>,
+}
+
+/// Statuses and costs of the test cases of a run, in test case order.
+struct RunSummary {
+    case_statuses: Vec<(String, CaseStatus)>,
+    /// Phase costs of the passing test cases.
+    case_steps: Vec<(String, PhaseSteps)>,
+    /// Process cost of each passing test case next to the summed cost of its sub-steps.
+    sub_step_totals: Vec<(String, u64, u64)>,
+}
+
+impl RunSummary {
+    /// Whether none of the test cases failed.
+    fn passed(&self) -> bool {
+        !self
+            .case_statuses
+            .iter()
+            .any(|(_, status)| status.is_failure())
     }
 }
 
-fn run_tests<T: OperationHandler>(
+/// Run every test case of the operation and report a summary of the run.
+///
+/// Up to `jobs` emulators run at once, while the outputs are checked and reported on the
+/// calling thread in test case order.
+fn run_tests<T: OperationHandler + Sync>(
     fork: &Fork,
     operation: &T,
-    excluded_cases: Vec<String>,
-    compare_specs: bool,
-    compare_recompute: bool,
-) {
-    let (base_dir, test_cases) = operation.load_test_cases(fork);
-    for test_case in test_cases {
-        if excluded_cases.contains(&test_case) {
-            info!("Skipping test case: {test_case}");
-            continue;
-        }
-        info!("[{operation}] Test case: {test_case}");
-        let case_dir = &base_dir.join(&test_case);
-        let input = operation.prepare_input(&case_dir);
-        let pre_state_ssz_bytes: Vec<u8> = ssz_from_file(&case_dir.join("pre.ssz_snappy"));
-        // Write input to file for ZISK guest
-        let build_dir = PathBuf::from("guest/build");
-        if !build_dir.exists() {
-            info!("Creating build directory at {:?}", build_dir);
-            fs::create_dir_all(&build_dir).expect("Failed to create build directory");
-        }
-        let input_path = build_dir.join("input.bin");
-
-        let _written = write_zisk_input(
-            pre_state_ssz_bytes.clone(),
-            bincode::serialize(&input).unwrap(),
-            input_path.to_str().unwrap(),
-        );
-        info!("Input written to {:?}", input_path);
-        info!("----- Cycle Tracker Start -----");
+    options: &RunOptions,
+) -> RunSummary {
+    let (base_dir, test_cases) = operation.load_test_cases(&options.test_case_dir, fork);
+    let fork_type = ForkType::from(fork.clone());
+    let mut case_steps: Vec<(String, PhaseSteps)> = Vec::new();
+    let mut sub_step_totals: Vec<(String, u64, u64)> = Vec::new();
+    let mut case_statuses: Vec<(String, CaseStatus)> = Vec::new();
+    let mut case_results: Vec<CaseResult> = Vec::new();
+    let run_info = RunInfo::collect(
+        fork.to_string(),
+        options.preset.to_string(),
+        operation.get_operation_category().to_string(),
+        operation.to_string(),
+        options.backend.as_ref(),
+    );
 
-        let output = Command::new("ziskemu")
-            .args([
-                "-e",
-                "target/riscv64ima-zisk-zkvm-elf/release/consenzisk_guest",
-                "-i",
-                input_path.to_str().unwrap(),
-                "-m",
-                "-x",
-            ])
-            .output()
-            .expect("Failed to run ZISK VM");
-        if !output.status.success() {
-            eprintln!(
-                "ZISK execution failed for test case {}!\n{}",
-                test_case,
-                String::from_utf8_lossy(&output.stderr)
-            );
-            std::process::exit(1);
+    let next_case = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..options.jobs.max(1) {
+            let sender = sender.clone();
+            let (base_dir, test_cases, next_case, stop) =
+                (&base_dir, &test_cases, &next_case, &stop);
+            scope.spawn(move || loop {
+                let index = next_case.fetch_add(1, Ordering::SeqCst);
+                if index >= test_cases.len() || stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                let test_case = &test_cases[index];
+                let run = (!options.excluded_cases.contains(test_case)).then(|| {
+                    let input_name = format!(
+                        "{fork}_{}_{operation}_{}",
+                        options.preset,
+                        test_case.replace('/', "_")
+                    );
+                    execute_case(
+                        fork_type,
+                        operation,
+                        options.backend.as_ref(),
+                        &base_dir.join(test_case),
+                        &input_name,
+                    )
+                });
+                if sender.send((index, run)).is_err() {
+                    break;
+                }
+            });
         }
+        drop(sender);
+
+        let mut pending: BTreeMap<usize, Option<Result<CaseRun, CaseStatus>>> = BTreeMap::new();
+        let mut next_report = 0;
+        'report: for (index, run) in receiver {
+            pending.insert(index, run);
+            while let Some(run) = pending.remove(&next_report) {
+                let test_case = test_cases[next_report].clone();
+                next_report += 1;
 
-        // Parse output from ZISK guest
-        let zisk_output = String::from_utf8_lossy(&output.stdout);
-        info!("ZISK output for {}: {}", test_case, zisk_output);
+                let Some(run) = run else {
+                    info!("Skipping test case: {test_case}");
+                    case_statuses.push((test_case, CaseStatus::Skipped));
+                    continue;
+                };
+                info!("[{operation}] Test case: {test_case}");
+                let case_dir = base_dir.join(&test_case);
+                let (mut case_result, status) = match run {
+                    Ok(run) => {
+                        let case_result = CaseResult::new(
+                            &run_info,
+                            &test_case,
+                            run.outputs.as_ref().ok().map(|(output, _)| output),
+                            run.pre_state_ssz_bytes.len(),
+                            run.input.operation_ssz_bytes().len(),
+                        );
+                        let status = match check_case(
+                            fork_type, operation, &case_dir, &test_case, options, run,
+                        ) {
+                            Ok((phase_steps, sub_steps)) => {
+                                if let Some(sub_steps) = sub_steps {
+                                    let full_steps = phase_steps.get(Phase::Process);
+                                    sub_step_totals.push((
+                                        test_case.clone(),
+                                        full_steps,
+                                        sub_steps,
+                                    ));
+                                }
+                                case_steps.push((test_case.clone(), phase_steps));
+                                CaseStatus::Passed
+                            }
+                            Err(status) => status,
+                        };
+                        (case_result, status)
+                    }
+                    // The test case files could not be read, so there is nothing to record
+                    Err(status) => (CaseResult::new(&run_info, &test_case, None, 0, 0), status),
+                };
+                info!("[{operation}] {test_case} status: {status}");
+                case_result.status = status.label().to_string();
+                case_results.push(case_result);
 
-        let new_state_root = parse_state_root_from_hex(&zisk_output);
-        if compare_specs {
-            assert_state_root_matches_specs(&new_state_root, &pre_state_ssz_bytes, &case_dir);
+                let failed = status.is_failure();
+                case_statuses.push((test_case, status));
+                if failed && options.fail_fast {
+                    stop.store(true, Ordering::SeqCst);
+                    break 'report;
+                }
+            }
         }
-        if compare_recompute {
-            assert_state_root_matches_recompute(&new_state_root, &pre_state_ssz_bytes, &input);
+    });
+
+    log_phase_summary(operation, options.backend.unit(), &case_steps);
+    if !sub_step_totals.is_empty() {
+        log_sub_step_summary(operation, &sub_step_totals);
+    }
+    log_case_summary(operation, &case_statuses);
+
+    if let Some(results_file) = &options.results_file {
+        match write_results(results_file, &case_results) {
+            Ok(()) => info!("[{operation}] Results written to {results_file:?}"),
+            Err(e) => eprintln!("Failed to write results to {results_file:?}: {e}"),
         }
+    }
+
+    RunSummary {
+        case_statuses,
+        case_steps,
+        sub_step_totals,
+    }
+}
 
-        info!("----- Cycle Tracker End -----");
+/// Run a test case and its sub-operations in the guest, each with its own input file.
+/// Fails without running the guest if the test case files cannot be read.
+fn execute_case<T: OperationHandler>(
+    fork_type: ForkType,
+    operation: &T,
+    backend: &dyn ExecutionBackend,
+    case_dir: &Path,
+    input_name: &str,
+) -> Result<CaseRun, CaseStatus> {
+    let input = operation
+        .prepare_input(case_dir)
+        .map_err(CaseStatus::VectorError)?;
+    let pre_state_ssz_bytes: Vec<u8> = operation
+        .load_pre_state(case_dir)
+        .map_err(CaseStatus::VectorError)?;
+
+    let outputs = run_guest(backend, fork_type, &pre_state_ssz_bytes, &input, input_name).and_then(
+        |output| {
+            let sub_outputs = operation
+                .sub_operations(fork_type)
+                .iter()
+                .enumerate()
+                .map(|(i, sub_input)| {
+                    let sub_input_name = format!("{input_name}_sub_{i}");
+                    run_guest(
+                        backend,
+                        fork_type,
+                        &pre_state_ssz_bytes,
+                        sub_input,
+                        &sub_input_name,
+                    )
+                })
+                .collect::<Result<Vec<GuestOutput>, CaseStatus>>()?;
+            Ok((output, sub_outputs))
+        },
+    );
+
+    Ok(CaseRun {
+        input,
+        pre_state_ssz_bytes,
+        outputs,
+    })
+}
+
+/// Check the guest outputs of a test case, returning the measured phase steps and, for
+/// operations made of sub-steps, the summed process steps of those sub-steps.
+fn check_case<T: OperationHandler>(
+    fork_type: ForkType,
+    operation: &T,
+    case_dir: &Path,
+    test_case: &str,
+    options: &RunOptions,
+    run: CaseRun,
+) -> Result<(PhaseSteps, Option<u64>), CaseStatus> {
+    let CaseRun {
+        input,
+        pre_state_ssz_bytes,
+        outputs,
+    } = run;
+    info!("----- Cycle Tracker Start -----");
+
+    let (output, sub_outputs) = outputs?;
+    let zisk_output = output.stdout;
+    info!(
+        "{} output for {}: {}",
+        options.backend.name(),
+        test_case,
+        zisk_output
+    );
+
+    let unit = options.backend.unit();
+    let phase_steps = output.metrics.phase_costs;
+    for (phase, steps) in phase_steps.iter() {
+        info!("[{operation}] {test_case} {phase}: {steps} {unit}");
+    }
+    if let (Some(allocations), Some(allocated_bytes)) =
+        (&output.metrics.allocations, &output.metrics.allocated_bytes)
+    {
+        info!(
+            "[{operation}] {test_case} {} allocations, {} allocated bytes",
+            allocations.total(),
+            allocated_bytes.total()
+        );
+    }
+
+    let public_outputs = output
+        .public_outputs
+        .ok_or_else(|| CaseStatus::GuestPanic("no public outputs were committed".to_string()))?;
+    check_public_inputs(
+        operation,
+        fork_type,
+        &public_outputs,
+        &pre_state_ssz_bytes,
+        &input,
+    )?;
+    let new_state_root = public_outputs.post_state_root;
+    let outcome = public_outputs.outcome;
+    info!("[{operation}] {test_case} outcome: {outcome}");
+    if options.compare_specs && operation.has_spec_post_state() {
+        check_state_root_matches_specs(
+            fork_type,
+            &new_state_root,
+            outcome,
+            &pre_state_ssz_bytes,
+            case_dir,
+            operation.rejection_keeps_pre_state(),
+        )?;
+    }
+    if options.compare_specs && operation.checks_finalized_checkpoint() {
+        check_finalized_checkpoint_matches_specs(
+            fork_type,
+            &public_outputs.finalized_checkpoint,
+            case_dir,
+        )?;
+    }
+    if options.compare_specs && operation.checks_deltas() {
+        check_deltas_digest_matches_specs(&public_outputs.deltas_digest, outcome, case_dir)?;
+    }
+    if operation.checked_by_recompute() {
+        check_eq("outcome", outcome, Outcome::Success)?;
+    }
+    if options.compare_recompute || operation.checked_by_recompute() {
+        check_state_root_matches_recompute(
+            fork_type,
+            &new_state_root,
+            outcome,
+            &pre_state_ssz_bytes,
+            &input,
+        )?;
+    }
+
+    info!("----- Cycle Tracker End -----");
+
+    if sub_outputs.is_empty() {
+        return Ok((phase_steps, None));
+    }
+    // A rejected sub-step stops early, so its cost would understate the sum
+    for sub_output in &sub_outputs {
+        let sub_public_outputs = sub_output.public_outputs.ok_or_else(|| {
+            CaseStatus::GuestPanic("no public outputs were committed by a sub-step".to_string())
+        })?;
+        check_eq(
+            "sub-step outcome",
+            sub_public_outputs.outcome,
+            Outcome::Success,
+        )?;
+    }
+    let sub_steps: u64 = sub_outputs
+        .iter()
+        .map(|output| output.metrics.phase_costs.get(Phase::Process))
+        .sum();
+    let full_steps = phase_steps.get(Phase::Process);
+    info!("[{operation}] {test_case} {full_steps} {unit}, sub-steps: {sub_steps} {unit}");
+
+    Ok((phase_steps, Some(sub_steps)))
+}
+
+/// Encode the guest input for a test case and execute it on the backend.
+fn run_guest(
+    backend: &dyn ExecutionBackend,
+    fork_type: ForkType,
+    pre_state_ssz_bytes: &[u8],
+    input: &OperationInput,
+    input_name: &str,
+) -> Result<GuestOutput, CaseStatus> {
+    let zisk_input = ZiskInput::new(fork_type, pre_state_ssz_bytes.to_vec(), input);
+    backend.execute(input_name, &zisk_input.encode())
+}
+
+fn log_sub_step_summary<T: OperationHandler>(
+    operation: &T,
+    sub_step_totals: &[(String, u64, u64)],
+) {
+    info!(
+        "[{operation}] {} vs sum of sub-steps: test_case | full | sub-steps | ratio",
+        Phase::Process
+    );
+    for (test_case, full_steps, sub_steps) in sub_step_totals {
+        let ratio = *full_steps as f64 / (*sub_steps).max(1) as f64;
+        info!("[{operation}] {test_case} | {full_steps} | {sub_steps} | {ratio:.3}");
+    }
+}
+
+fn log_phase_summary<T: OperationHandler>(
+    operation: &T,
+    unit: &str,
+    case_steps: &[(String, PhaseSteps)],
+) {
+    let header: Vec<&str> = Phase::ALL.iter().map(|phase| phase.name()).collect();
+    info!(
+        "[{operation}] Per-phase {unit}: test_case | {} | total",
+        header.join(" | ")
+    );
+    for (test_case, phase_steps) in case_steps {
+        let steps: Vec<String> = phase_steps
+            .iter()
+            .map(|(_, steps)| steps.to_string())
+            .collect();
+        info!(
+            "[{operation}] {test_case} | {} | {}",
+            steps.join(" | "),
+            phase_steps.total()
+        );
     }
 }
 
@@ -149,41 +513,39 @@ fn setup_log() {
         .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
         .init();
 }
-fn write_zisk_input(
-    pre_state_ssz_bytes: Vec<u8>,
-    operation_input: Vec<u8>,
-    input_path: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let zisk_input = ZiskInput {
-        pre_state_ssz_bytes,
-        operation_input,
-    };
-
-    let serialized_input = bincode::serialize(&zisk_input)?;
-    std::fs::write(input_path, serialized_input)?;
-    Ok(())
-}
-fn parse_args() -> (Fork, Operation, Vec<String>, bool, bool) {
+fn parse_args() -> (Fork, Preset, Operation, RunOptions) {
     let args = Args::parse();
+    let preset = Preset::from(args.preset.preset);
 
     (
         args.fork.fork,
+        preset,
         args.operation.operation,
-        args.excluded_cases,
-        args.compare_specs,
-        args.compare_recompute,
+        RunOptions {
+            test_case_dir: args.spec_tests.test_case_dir(preset),
+            preset,
+            excluded_cases: args.excluded_cases,
+            compare_specs: args.compare_specs,
+            compare_recompute: args.compare_recompute,
+            fail_fast: args.fail_fast,
+            jobs: args.jobs,
+            results_file: args.results_file,
+            backend: args.backend.into(),
+        },
     )
 }
 
-fn assert_state_root_matches_specs(
+fn check_state_root_matches_specs(
+    fork: ForkType,
     new_state_root: &Hash256,
+    outcome: Outcome,
     pre_state_ssz_bytes: &[u8],
-    case_dir: &PathBuf,
-) {
-    let post_state_opt: Option<BeaconState> = {
+    case_dir: &Path,
+    rejection_keeps_pre_state: bool,
+) -> Result<(), CaseStatus> {
+    let post_state_opt: Option<ForkedBeaconState> = {
         if case_dir.join("post.ssz_snappy").exists() {
-            let ssz_bytes: Vec<u8> = ssz_from_file(&case_dir.join("post.ssz_snappy"));
-            Some(from_ssz_bytes(&ssz_bytes).unwrap())
+            Some(read_state(fork, &case_dir.join("post.ssz_snappy"))?)
         } else {
             None
         }
@@ -192,59 +554,370 @@ fn assert_state_root_matches_specs(
     match post_state_opt {
         // If the specs provide post_state, compare the computed root against post_state's root
         Some(post_state) => {
-            info!("post_state provided. The state root should be mutated.");
-            assert_eq!(*new_state_root, post_state.tree_hash_root());
+            info!("post_state provided. The operation should succeed and mutate the state root.");
+            check_eq("outcome", outcome, Outcome::Success)?;
+            check_eq(
+                "post_state_root",
+                *new_state_root,
+                post_state.tree_hash_root(),
+            )?;
             info!("Execution is correct! State mutated and the roots match.");
         }
         // If the specs does not contain a post_state, compare the computed root against pre_state's root
         None => {
-            info!("post_state not provided. The state root should not be mutated.");
-            let pre_state: BeaconState = from_ssz_bytes(&pre_state_ssz_bytes).unwrap();
-            assert_eq!(*new_state_root, pre_state.tree_hash_root());
-            info!("Execution is correct! State should not be mutated and the roots match.");
+            info!("post_state not provided. The operation should be rejected.");
+            check_eq("outcome", outcome, Outcome::Rejected)?;
+            if !rejection_keeps_pre_state {
+                info!("Execution is correct! Operation rejected.");
+                return Ok(());
+            }
+            let pre_state = decode_state(fork, pre_state_ssz_bytes)?;
+            check_eq(
+                "post_state_root",
+                *new_state_root,
+                pre_state.tree_hash_root(),
+            )?;
+            info!("Execution is correct! Operation rejected and the roots match.");
         }
     }
+    Ok(())
 }
 
-fn parse_state_root_from_hex(hex_output: &str) -> Hash256 {
-    // The guest program outputs the state root as hex string
-    // Find the hex string in the output (it should be a 64-character hex string)
-    let lines: Vec<&str> = hex_output.lines().collect();
-
-    for line in lines {
-        let trimmed = line.trim();
-        // Look for a 64-character hex string (32 bytes * 2 chars per byte)
-        if trimmed.len() == 64 && trimmed.chars().all(|c| c.is_ascii_hexdigit()) {
-            // Parse hex string to bytes
-            let mut bytes = [0u8; 32];
-            for (i, chunk) in trimmed.as_bytes().chunks(2).enumerate() {
-                let hex_str = std::str::from_utf8(chunk).unwrap();
-                bytes[i] = u8::from_str_radix(hex_str, 16).unwrap();
-            }
-            return Hash256::from(bytes);
-        }
-    }
+/// Check the committed pre-state and operation roots against the roots of the test case inputs.
+fn check_public_inputs<T: OperationHandler>(
+    operation: &T,
+    fork: ForkType,
+    public_outputs: &PublicOutputs,
+    pre_state_ssz_bytes: &[u8],
+    input: &OperationInput,
+) -> Result<(), CaseStatus> {
+    let pre_state_root = operation
+        .pre_state_root(fork, pre_state_ssz_bytes, input)
+        .map_err(CaseStatus::VectorError)?;
+    let operation_root = input.operation_root().unwrap_or_default();
 
-    panic!(
-        "Could not find valid hex state root in output: {}",
-        hex_output
+    info!(
+        "pre_state_root: {}, operation_root: {}",
+        public_outputs.pre_state_root, public_outputs.operation_root
     );
+    check_eq(
+        "pre_state_root",
+        public_outputs.pre_state_root,
+        pre_state_root,
+    )?;
+    check_eq(
+        "operation_root",
+        public_outputs.operation_root,
+        operation_root,
+    )?;
+    info!("Public inputs are correct! Pre-state and operation roots match the test case's.");
+    Ok(())
+}
+
+fn check_finalized_checkpoint_matches_specs(
+    fork: ForkType,
+    finalized_checkpoint: &FinalizedCheckpoint,
+    case_dir: &Path,
+) -> Result<(), CaseStatus> {
+    let post_state = read_state(fork, &case_dir.join("post.ssz_snappy"))?;
+
+    info!("Finalized checkpoint: {finalized_checkpoint}");
+    check_eq(
+        "finalized checkpoint",
+        *finalized_checkpoint,
+        post_state.finalized_checkpoint(),
+    )?;
+    info!("Execution is correct! Finalized checkpoint matches post_state's.");
+    Ok(())
+}
+
+fn check_deltas_digest_matches_specs(
+    digest: &Hash256,
+    outcome: Outcome,
+    case_dir: &Path,
+) -> Result<(), CaseStatus> {
+    let encoded_deltas: Vec<Vec<u8>> = DELTAS_FILES
+        .iter()
+        .map(|file| ssz_from_file(&case_dir.join(format!("{file}.ssz_snappy"))))
+        .collect::<Result<_, _>>()
+        .map_err(CaseStatus::VectorError)?;
+
+    info!("Deltas digest: {digest}");
+    check_eq("outcome", outcome, Outcome::Success)?;
+    check_eq("deltas digest", *digest, deltas_digest(&encoded_deltas))?;
+    info!("Execution is correct! Deltas digest matches the specs' deltas.");
+    Ok(())
 }
 
-fn assert_state_root_matches_recompute(
+fn check_state_root_matches_recompute(
+    fork: ForkType,
     new_state_root: &Hash256,
+    outcome: Outcome,
     pre_state_ssz_bytes: &[u8],
     input: &OperationInput,
-) {
-    let mut state: BeaconState = from_ssz_bytes(&pre_state_ssz_bytes).unwrap();
-
-    input.process(&mut state);
+) -> Result<(), CaseStatus> {
+    let (state, result) = match input {
+        // A fork upgrade starts from the previous-fork state carried by the operation
+        OperationInput::ForkUpgrade(wrapper) => {
+            let pre_state = wrapper
+                .decode_pre_state(fork)
+                .map_err(|e| CaseStatus::VectorError(format!("invalid pre-state: {e}")))?;
+            let state = pre_state.upgrade().map_err(|e| {
+                CaseStatus::VectorError(format!("pre-state cannot be upgraded: {e}"))
+            })?;
+            (state, Ok(()))
+        }
+        _ => {
+            let mut state = decode_state(fork, pre_state_ssz_bytes)?;
+            let result = input.process(&mut state);
+            (state, result)
+        }
+    };
+    check_eq("outcome", outcome, Outcome::from(&result))?;
 
     let recomputed_state_root = state.tree_hash_root();
 
     println!("recomputed_state_root: {}", recomputed_state_root);
     println!("new_state_root: {}", new_state_root);
 
-    assert_eq!(*new_state_root, recomputed_state_root);
+    check_eq("post_state_root", *new_state_root, recomputed_state_root)?;
     info!("Execution is correct! State roots match host's recomputed state root.");
+    Ok(())
+}
+
+/// Decode a beacon state of the test case, failing the case if it is not valid SSZ.
+fn decode_state(fork: ForkType, ssz_bytes: &[u8]) -> Result<ForkedBeaconState, CaseStatus> {
+    ForkedBeaconState::from_ssz_bytes(fork, ssz_bytes)
+        .map_err(|e| CaseStatus::VectorError(format!("invalid state: {e:?}")))
+}
+
+/// Read and decode a beacon state file of the test case.
+fn read_state(fork: ForkType, path: &Path) -> Result<ForkedBeaconState, CaseStatus> {
+    let ssz_bytes = ssz_from_file(path).map_err(CaseStatus::VectorError)?;
+    decode_state(fork, &ssz_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use ream_lib::input::SlotsWrapper;
+
+    use super::*;
+    use crate::backend::{fake::FakeBackend, Metrics};
+
+    /// Operation whose test cases are named after the output the fake backend commits for
+    /// them: `pass`, `mismatch`, `panic` or `missing` files.
+    struct FakeOperation {
+        test_cases: Vec<&'static str>,
+        sub_operations: usize,
+    }
+
+    impl fmt::Display for FakeOperation {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("fake")
+        }
+    }
+
+    impl OperationHandler for FakeOperation {
+        fn prepare_input(&self, case_dir: &Path) -> Result<OperationInput, String> {
+            if case_dir.to_string_lossy().ends_with("missing") {
+                return Err(format!("Could not read file: {case_dir:?}"));
+            }
+            Ok(OperationInput::Slots(SlotsWrapper { slots: 1 }))
+        }
+
+        fn load_test_cases(&self, _test_case_dir: &Path, _fork: &Fork) -> (PathBuf, Vec<String>) {
+            let test_cases = self
+                .test_cases
+                .iter()
+                .map(|case| case.to_string())
+                .collect();
+            (PathBuf::from("vectors"), test_cases)
+        }
+
+        fn get_operation_category(&self) -> &'static str {
+            "fake"
+        }
+
+        fn sub_operations(&self, _fork: ForkType) -> Vec<OperationInput> {
+            (0..self.sub_operations)
+                .map(|_| OperationInput::Slots(SlotsWrapper { slots: 1 }))
+                .collect()
+        }
+
+        fn load_pre_state(&self, _case_dir: &Path) -> Result<Vec<u8>, String> {
+            Ok(Vec::new())
+        }
+
+        fn pre_state_root(
+            &self,
+            _fork: ForkType,
+            _pre_state_ssz_bytes: &[u8],
+            _input: &OperationInput,
+        ) -> Result<Hash256, String> {
+            Ok(Hash256::repeat_byte(0x11))
+        }
+
+        fn has_spec_post_state(&self) -> bool {
+            false
+        }
+    }
+
+    /// Commit the expected roots for `pass` cases and a wrong pre-state root for `mismatch`
+    /// cases, abort `panic` cases and reject the sub-operations of `reject` cases.
+    /// Operations cost 100 steps and sub-operations 30.
+    fn respond(input_name: &str, zisk_input: &ZiskInput) -> Result<GuestOutput, CaseStatus> {
+        let (test_case, is_sub_operation) = match input_name.split_once("_sub_") {
+            Some((test_case, _)) => (test_case, true),
+            None => (input_name, false),
+        };
+        if test_case.ends_with("panic") {
+            return Err(CaseStatus::GuestPanic("guest aborted".to_string()));
+        }
+        let pre_state_root = if test_case.ends_with("mismatch") {
+            Hash256::repeat_byte(0xee)
+        } else {
+            Hash256::repeat_byte(0x11)
+        };
+        let outcome = if is_sub_operation && test_case.ends_with("reject") {
+            Outcome::Rejected
+        } else {
+            Outcome::Success
+        };
+        let mut phase_costs = PhaseSteps::default();
+        phase_costs.add(Phase::Process, if is_sub_operation { 30 } else { 100 });
+        Ok(GuestOutput {
+            stdout: String::new(),
+            public_outputs: Some(PublicOutputs {
+                pre_state_root,
+                operation_root: zisk_input.operation().unwrap().operation_root().unwrap(),
+                post_state_root: Hash256::ZERO,
+                deltas_digest: Hash256::ZERO,
+                finalized_checkpoint: FinalizedCheckpoint::default(),
+                outcome,
+            }),
+            metrics: Metrics {
+                phase_costs,
+                ..Metrics::default()
+            },
+        })
+    }
+
+    fn options(jobs: usize, fail_fast: bool) -> RunOptions {
+        RunOptions {
+            test_case_dir: PathBuf::new(),
+            preset: Preset::COMPILED,
+            excluded_cases: vec!["excluded".to_string()],
+            compare_specs: true,
+            compare_recompute: false,
+            fail_fast,
+            jobs,
+            results_file: None,
+            backend: Box::new(FakeBackend::new(respond)),
+        }
+    }
+
+    fn run(operation: &FakeOperation, options: &RunOptions) -> RunSummary {
+        run_tests(&Fork::Electra, operation, options)
+    }
+
+    #[test]
+    fn run_reports_every_case_in_order_with_its_status() {
+        let operation = FakeOperation {
+            test_cases: vec![
+                "a_pass",
+                "b_mismatch",
+                "c_panic",
+                "d_missing",
+                "excluded",
+                "f_pass",
+            ],
+            sub_operations: 0,
+        };
+
+        let summary = run(&operation, &options(4, false));
+
+        let test_cases: Vec<&str> = summary
+            .case_statuses
+            .iter()
+            .map(|(test_case, _)| test_case.as_str())
+            .collect();
+        assert_eq!(test_cases, operation.test_cases);
+        let statuses: Vec<&CaseStatus> = summary
+            .case_statuses
+            .iter()
+            .map(|(_, status)| status)
+            .collect();
+        assert_eq!(statuses[0], &CaseStatus::Passed);
+        assert!(
+            matches!(statuses[1], CaseStatus::RootMismatch(reason) if reason.contains("pre_state_root"))
+        );
+        assert!(matches!(statuses[2], CaseStatus::GuestPanic(_)));
+        assert!(matches!(statuses[3], CaseStatus::VectorError(_)));
+        assert_eq!(statuses[4], &CaseStatus::Skipped);
+        assert_eq!(statuses[5], &CaseStatus::Passed);
+        assert!(!summary.passed());
+
+        let passing: Vec<&str> = summary
+            .case_steps
+            .iter()
+            .map(|(test_case, _)| test_case.as_str())
+            .collect();
+        assert_eq!(passing, ["a_pass", "f_pass"]);
+    }
+
+    #[test]
+    fn fail_fast_stops_at_the_first_failing_case() {
+        let operation = FakeOperation {
+            test_cases: vec!["a_pass", "b_mismatch", "c_pass", "d_pass"],
+            sub_operations: 0,
+        };
+
+        let summary = run(&operation, &options(1, true));
+
+        let statuses: Vec<&str> = summary
+            .case_statuses
+            .iter()
+            .map(|(_, status)| status.label())
+            .collect();
+        assert_eq!(statuses, ["passed", "root mismatch"]);
+        assert!(!summary.passed());
+    }
+
+    #[test]
+    fn sub_step_totals_sum_the_process_cost_of_every_sub_operation() {
+        let operation = FakeOperation {
+            test_cases: vec!["a_pass", "b_pass", "excluded"],
+            sub_operations: 3,
+        };
+
+        let summary = run(&operation, &options(2, false));
+
+        assert!(summary.passed());
+        assert_eq!(
+            summary.sub_step_totals,
+            [
+                ("a_pass".to_string(), 100, 90),
+                ("b_pass".to_string(), 100, 90),
+            ]
+        );
+        assert_eq!(summary.case_steps[0].1.get(Phase::Process), 100);
+    }
+
+    #[test]
+    fn a_rejected_sub_operation_fails_the_case() {
+        let operation = FakeOperation {
+            test_cases: vec!["a_pass", "b_reject"],
+            sub_operations: 2,
+        };
+
+        let summary = run(&operation, &options(1, false));
+
+        assert_eq!(summary.case_statuses[0].1, CaseStatus::Passed);
+        assert!(
+            matches!(&summary.case_statuses[1].1, CaseStatus::RootMismatch(reason) if reason.contains("sub-step outcome"))
+        );
+        assert_eq!(summary.sub_step_totals.len(), 1);
+    }
 }
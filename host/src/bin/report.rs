@@ -0,0 +1,88 @@
+use std::fmt;
+
+use tracing::info;
+
+/// Result of running a single test case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaseStatus {
+    Passed,
+    /// A root, checkpoint, digest or outcome reported by the guest differs from the expected one.
+    RootMismatch(String),
+    /// The guest aborted before committing its public outputs.
+    GuestPanic(String),
+    /// The emulator could not be run or failed on its own.
+    EmulatorError(String),
+    /// The test case files are missing or could not be decoded on the host.
+    VectorError(String),
+    /// The test case was excluded from the run.
+    Skipped,
+}
+
+impl CaseStatus {
+    pub fn is_failure(&self) -> bool {
+        !matches!(self, CaseStatus::Passed | CaseStatus::Skipped)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CaseStatus::Passed => "passed",
+            CaseStatus::RootMismatch(_) => "root mismatch",
+            CaseStatus::GuestPanic(_) => "guest panic",
+            CaseStatus::EmulatorError(_) => "emulator error",
+            CaseStatus::VectorError(_) => "vector error",
+            CaseStatus::Skipped => "skipped",
+        }
+    }
+}
+
+impl fmt::Display for CaseStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaseStatus::RootMismatch(reason)
+            | CaseStatus::GuestPanic(reason)
+            | CaseStatus::EmulatorError(reason)
+            | CaseStatus::VectorError(reason) => write!(f, "{}: {reason}", self.label()),
+            CaseStatus::Passed | CaseStatus::Skipped => f.write_str(self.label()),
+        }
+    }
+}
+
+/// Compare a value reported by the guest against the expected one.
+pub fn check_eq<T: PartialEq + fmt::Display>(
+    what: &str,
+    actual: T,
+    expected: T,
+) -> Result<(), CaseStatus> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(CaseStatus::RootMismatch(format!(
+            "{what} is {actual}, expected {expected}"
+        )))
+    }
+}
+
+/// Log the status of every test case, followed by the number of cases per status.
+pub fn log_case_summary(operation: &impl fmt::Display, case_statuses: &[(String, CaseStatus)]) {
+    info!("[{operation}] Test case results: test_case | status");
+    for (test_case, status) in case_statuses {
+        info!("[{operation}] {test_case} | {status}");
+    }
+
+    let count = |label: &str| {
+        case_statuses
+            .iter()
+            .filter(|(_, status)| status.label() == label)
+            .count()
+    };
+    info!(
+        "[{operation}] {} passed, {} root mismatch, {} guest panic, {} emulator error, \
+         {} vector error, {} skipped",
+        count("passed"),
+        count("root mismatch"),
+        count("guest panic"),
+        count("emulator error"),
+        count("vector error"),
+        count("skipped"),
+    );
+}
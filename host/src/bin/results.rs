@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use ream_lib::phase::{Phase, PhaseSteps};
+use serde::{Deserialize, Serialize};
+
+use crate::backend::{ExecutionBackend, GuestOutput};
+
+/// Values shared by every record of a run, identifying what was benchmarked.
+#[derive(Debug, Clone)]
+pub struct RunInfo {
+    pub backend: String,
+    pub fork: String,
+    pub preset: String,
+    pub category: String,
+    pub operation: String,
+    pub guest_elf_sha256: Option<String>,
+    pub git_revision: Option<String>,
+}
+
+impl RunInfo {
+    pub fn collect(
+        fork: String,
+        preset: String,
+        category: String,
+        operation: String,
+        backend: &dyn ExecutionBackend,
+    ) -> Self {
+        Self {
+            backend: backend.name().to_string(),
+            fork,
+            preset,
+            category,
+            operation,
+            guest_elf_sha256: backend.program_hash(),
+            git_revision: git_revision(),
+        }
+    }
+}
+
+/// Benchmark record of a single executed test case.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseResult {
+    pub backend: String,
+    pub fork: String,
+    pub preset: String,
+    pub category: String,
+    pub operation: String,
+    pub test_case: String,
+    pub status: String,
+    /// Outcome committed by the guest, if it committed its public outputs.
+    pub outcome: Option<String>,
+    pub steps: Option<u64>,
+    pub duration_secs: Option<f64>,
+    /// Cost per guest phase, keyed by phase name, in the unit of the backend.
+    pub phase_steps: BTreeMap<String, u64>,
+    /// Allocations per guest phase, for backends running on the host.
+    #[serde(default)]
+    pub phase_allocations: BTreeMap<String, u64>,
+    pub allocations: Option<u64>,
+    pub allocated_bytes: Option<u64>,
+    pub pre_state_bytes: usize,
+    pub operation_bytes: usize,
+    pub guest_elf_sha256: Option<String>,
+    pub git_revision: Option<String>,
+}
+
+impl CaseResult {
+    /// Build the record of a test case from the output of its operation run.
+    pub fn new(
+        run_info: &RunInfo,
+        test_case: &str,
+        output: Option<&GuestOutput>,
+        pre_state_bytes: usize,
+        operation_bytes: usize,
+    ) -> Self {
+        let metrics = output.map(|output| &output.metrics);
+        let by_phase_name = |per_phase: &PhaseSteps| -> BTreeMap<String, u64> {
+            per_phase
+                .iter()
+                .map(|(phase, count)| (phase.name().to_string(), count))
+                .collect()
+        };
+        Self {
+            backend: run_info.backend.clone(),
+            fork: run_info.fork.clone(),
+            preset: run_info.preset.clone(),
+            category: run_info.category.clone(),
+            operation: run_info.operation.clone(),
+            test_case: test_case.to_string(),
+            status: String::new(),
+            outcome: output
+                .and_then(|output| output.public_outputs)
+                .map(|outputs| outputs.outcome.to_string()),
+            steps: metrics.and_then(|metrics| metrics.steps),
+            duration_secs: metrics.and_then(|metrics| metrics.duration_secs),
+            phase_steps: metrics
+                .map(|metrics| by_phase_name(&metrics.phase_costs))
+                .unwrap_or_default(),
+            phase_allocations: metrics
+                .and_then(|metrics| metrics.allocations.as_ref())
+                .map(by_phase_name)
+                .unwrap_or_default(),
+            allocations: metrics
+                .and_then(|metrics| metrics.allocations.as_ref())
+                .map(PhaseSteps::total),
+            allocated_bytes: metrics
+                .and_then(|metrics| metrics.allocated_bytes.as_ref())
+                .map(PhaseSteps::total),
+            pre_state_bytes,
+            operation_bytes,
+            guest_elf_sha256: run_info.guest_elf_sha256.clone(),
+            git_revision: run_info.git_revision.clone(),
+        }
+    }
+}
+
+/// Write the records of a run, as CSV if the file has a `.csv` extension and as a JSON
+/// array otherwise.
+pub fn write_results(
+    path: &Path,
+    results: &[CaseResult],
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let contents = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("csv") => to_csv(results),
+        _ => serde_json::to_string_pretty(results)?,
+    };
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+/// Read the records of a run from a JSON results file.
+pub fn read_results(path: &Path) -> Result<Vec<CaseResult>, Box<dyn std::error::Error>> {
+    if path.extension().and_then(|extension| extension.to_str()) == Some("csv") {
+        return Err(format!("{path:?} is a CSV file, only JSON results can be read").into());
+    }
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read results from {path:?}: {e}"))?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+/// Render the records as CSV, with one column per phase for the phase costs.
+fn to_csv(results: &[CaseResult]) -> String {
+    let mut header = vec![
+        "backend",
+        "fork",
+        "preset",
+        "category",
+        "operation",
+        "test_case",
+        "status",
+        "outcome",
+        "steps",
+        "duration_secs",
+    ];
+    header.extend(Phase::ALL.iter().map(|phase| phase.name()));
+    header.extend([
+        "allocations",
+        "allocated_bytes",
+        "pre_state_bytes",
+        "operation_bytes",
+        "guest_elf_sha256",
+        "git_revision",
+    ]);
+
+    let mut csv = header.join(",") + "\n";
+    for result in results {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        let mut row = vec![
+            result.backend.clone(),
+            result.fork.clone(),
+            result.preset.clone(),
+            result.category.clone(),
+            result.operation.clone(),
+            result.test_case.clone(),
+            result.status.clone(),
+            optional(result.outcome.clone()),
+            optional(result.steps.map(|steps| steps.to_string())),
+            optional(result.duration_secs.map(|duration| duration.to_string())),
+        ];
+        let phase_steps = |phase: &Phase| result.phase_steps.get(phase.name()).copied();
+        row.extend(
+            Phase::ALL
+                .iter()
+                .map(|phase| optional(phase_steps(phase).map(|steps| steps.to_string()))),
+        );
+        row.extend([
+            optional(result.allocations.map(|count| count.to_string())),
+            optional(result.allocated_bytes.map(|count| count.to_string())),
+            result.pre_state_bytes.to_string(),
+            result.operation_bytes.to_string(),
+            optional(result.guest_elf_sha256.clone()),
+            optional(result.git_revision.clone()),
+        ]);
+        let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        csv += &(row.join(",") + "\n");
+    }
+    csv
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn git_revision() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -1,9 +1,10 @@
 #!/bin/bash
 
-TARGET="mainnet.tar.gz"
+PRESET="${PRESET:-mainnet}"
+TARGET="$PRESET.tar.gz"
 SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
 PARENT_DIR="$(dirname "$SCRIPT_DIR")"
-EXTRACT_DIR="$PARENT_DIR/mainnet"
+EXTRACT_DIR="$PARENT_DIR/$PRESET"
 LATEST_RELEASE_URL="https://api.github.com/repos/ethereum/consensus-spec-tests/releases/latest"
 
 download_and_extract() {
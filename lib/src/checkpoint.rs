@@ -0,0 +1,16 @@
+use std::fmt;
+
+use alloy_primitives::B256;
+
+/// Finalized checkpoint of a beacon state, as committed by the guest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FinalizedCheckpoint {
+    pub epoch: u64,
+    pub root: B256,
+}
+
+impl fmt::Display for FinalizedCheckpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "epoch {} root {}", self.epoch, self.root)
+    }
+}
@@ -0,0 +1,110 @@
+use alloy_primitives::B256;
+
+use crate::{
+    input::{DecodedOperation, ProcessError},
+    outcome::Outcome,
+    phase::Phase,
+    protocol::{PublicOutputs, ZiskInputRef},
+    state::ForkedBeaconState,
+};
+
+/// Measures the cost of each phase of an execution, e.g. in zkVM steps or in wall-clock time.
+pub trait PhaseMeter {
+    fn measure<T>(&mut self, phase: Phase, f: impl FnOnce() -> T) -> T;
+}
+
+/// State transition of a guest input, up to the values it commits.
+pub struct Execution {
+    pub pre_state_root: B256,
+    pub operation_root: B256,
+    pub post_state: ForkedBeaconState,
+    pub post_state_root: B256,
+    pub result: Result<(), ProcessError>,
+    /// Digest of the computed deltas, for rewards operations.
+    pub deltas_digest: Option<B256>,
+}
+
+impl Execution {
+    pub fn public_outputs(&self) -> PublicOutputs {
+        PublicOutputs {
+            pre_state_root: self.pre_state_root,
+            operation_root: self.operation_root,
+            post_state_root: self.post_state_root,
+            deltas_digest: self.deltas_digest.unwrap_or_default(),
+            finalized_checkpoint: self.post_state.finalized_checkpoint(),
+            outcome: Outcome::from(&self.result),
+        }
+    }
+}
+
+/// Decode a guest input and run its state transition, measuring every phase but the output.
+///
+/// Panics on inputs that cannot be decoded, as the guest aborts on them.
+pub fn execute(input_bytes: &[u8], meter: &mut impl PhaseMeter) -> Execution {
+    // Unwrapping the input container only splits it into borrowed fields, so the pre-state
+    // bytes are first read when the pre-state is deserialized.
+    let zisk_input = meter.measure(Phase::DeserializeOperation, || {
+        ZiskInputRef::decode(input_bytes).expect("Failed to deserialize input")
+    });
+    let fork = zisk_input.fork().expect("Failed to deserialize fork");
+
+    let operation_input = meter.measure(Phase::DeserializeOperation, || {
+        zisk_input.operation().unwrap()
+    });
+
+    // The operation is decoded once, then the same value is hashed and processed.
+    let operation = meter.measure(Phase::DeserializeOperation, || operation_input.decode());
+
+    // An operation that cannot be decoded commits a zero root and fails when processed.
+    let operation_root = meter.measure(Phase::MerkleizeInputs, || {
+        operation
+            .as_ref()
+            .map(DecodedOperation::operation_root)
+            .unwrap_or_default()
+    });
+
+    // State transition of the beacon state.
+    let mut deltas_digest = None;
+    let (pre_state_root, post_state, result) = match operation {
+        // A fork upgrade starts from the previous-fork state carried by the operation.
+        Ok(DecodedOperation::ForkUpgrade(wrapper)) => {
+            let pre_state = meter.measure(Phase::DeserializePreState, || {
+                wrapper.decode_pre_state(fork).unwrap()
+            });
+            let pre_state_root =
+                meter.measure(Phase::MerkleizeInputs, || pre_state.tree_hash_root());
+            let state = meter
+                .measure(Phase::Process, || pre_state.upgrade())
+                .expect("Failed to upgrade the pre-fork state");
+            (pre_state_root, state, Ok(()))
+        }
+        operation => {
+            let mut state = meter.measure(Phase::DeserializePreState, || {
+                ForkedBeaconState::from_ssz_bytes(fork, zisk_input.pre_state_ssz_bytes).unwrap()
+            });
+            let pre_state_root = meter.measure(Phase::MerkleizeInputs, || state.tree_hash_root());
+            // Rewards leave the state untouched and commit a digest of the computed deltas next to
+            // the roots.
+            let result = meter.measure(Phase::Process, || match operation {
+                Ok(DecodedOperation::Rewards(wrapper)) => wrapper
+                    .deltas_digest(&state)
+                    .map(|digest| deltas_digest = Some(digest)),
+                Ok(operation) => operation.process(&mut state),
+                Err(err) => Err(err),
+            });
+            (pre_state_root, state, result)
+        }
+    };
+
+    // Merkleize the processed state
+    let post_state_root = meter.measure(Phase::Merkleize, || post_state.tree_hash_root());
+
+    Execution {
+        pre_state_root,
+        operation_root,
+        post_state,
+        post_state_root,
+        result,
+        deltas_digest,
+    }
+}
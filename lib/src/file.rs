@@ -1,18 +1,16 @@
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
 use crate::snappy::decode_snappy;
 
-pub fn ssz_from_file(path: &Path) -> Vec<u8> {
+pub fn ssz_from_file(path: &Path) -> Result<Vec<u8>, String> {
     let raw_bytes =
-        std::fs::read(path).unwrap_or_else(|e| panic!("Could not read file: {:?}: {}", path, e));
+        std::fs::read(path).map_err(|e| format!("Could not read file: {:?}: {}", path, e))?;
 
-    decode_snappy(&raw_bytes).unwrap_or_else(|e| {
-        panic!("Could not decode snappy {:?}: {}", path, e);
-    })
+    decode_snappy(&raw_bytes).map_err(|e| format!("Could not decode snappy {:?}: {}", path, e))
 }
 
-pub fn get_test_cases(base_dir: &PathBuf) -> Vec<String> {
+pub fn get_test_cases(base_dir: &Path) -> Vec<String> {
     let mut test_cases = Vec::new();
 
     if let Ok(entries) = fs::read_dir(base_dir) {
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForkType {
+    Electra,
+    Fulu,
+}
+
+impl ForkType {
+    /// Tag of the fork in the SSZ-encoded guest input.
+    pub fn tag(&self) -> u8 {
+        match self {
+            ForkType::Electra => 0,
+            ForkType::Fulu => 1,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(ForkType::Electra),
+            1 => Some(ForkType::Fulu),
+            _ => None,
+        }
+    }
+}
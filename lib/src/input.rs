@@ -1,24 +1,66 @@
-use ream_consensus::electra::beacon_state::BeaconState;
-use serde::{Deserialize, Serialize};
+use std::fmt;
 
-#[derive(Serialize, Deserialize, Debug)]
+use alloy_primitives::B256;
+use ream_consensus::execution_engine::mock_engine::MockExecutionEngine;
+use ssz::{DecodeError, Encode};
+use ssz_derive::{Decode, Encode};
+use tree_hash::{Hash256, TreeHash};
+
+use crate::{
+    fork::ForkType,
+    rewards::{attestation_deltas, deltas_digest},
+    ssz::from_ssz_bytes,
+    state::{for_each_fork, ForkedBeaconState, PreForkBeaconState},
+};
+
+#[derive(Debug)]
 pub enum OperationInput {
     Block(BlockOperationWrapper),
     Epoch(EpochOperationWrapper),
+    Blocks(BlocksWrapper),
+    Slots(SlotsWrapper),
+    Rewards(RewardsWrapper),
+    ForkUpgrade(ForkUpgradeWrapper),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Debug, Encode, Decode)]
 pub struct BlockOperationWrapper {
     pub operation_type: BlockOperationType,
     pub ssz_bytes: Vec<u8>,
+    /// Verdict of the mocked execution engine, only consulted by `ExecutionPayload`.
+    pub execution_valid: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Debug, Encode, Decode)]
 pub struct EpochOperationWrapper {
     pub operation_type: EpochOperationType,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Debug, Encode, Decode)]
+pub struct BlocksWrapper {
+    /// SSZ-encoded signed beacon blocks, imported in order.
+    pub blocks: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Encode, Decode)]
+pub struct SlotsWrapper {
+    /// Number of slots to advance the state by.
+    pub slots: u64,
+}
+
+/// Attestation rewards and penalties, computed without mutating the state.
+/// Carries no data, so its operation bytes are empty.
+#[derive(Debug)]
+pub struct RewardsWrapper {}
+
+#[derive(Debug, Encode, Decode)]
+pub struct ForkUpgradeWrapper {
+    /// SSZ-encoded state of the previous fork, upgraded to the fork of the run.
+    pub pre_state_ssz_bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode)]
+#[ssz(enum_behaviour = "tag")]
 pub enum BlockOperationType {
     Attestation,
     AttesterSlashing,
@@ -32,7 +74,8 @@ pub enum BlockOperationType {
     Withdrawals,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Debug, Clone, Copy, Encode, Decode)]
+#[ssz(enum_behaviour = "tag")]
 pub enum EpochOperationType {
     JustificationAndFinalization,
     InactivityUpdates,
@@ -47,120 +90,424 @@ pub enum EpochOperationType {
     RandaoMixesReset,
     HistoricalSummariesUpdate,
     ParticipationFlagUpdates,
+    SyncCommitteeUpdates,
+    /// Only part of the epoch transition from Fulu on.
+    ProposerLookahead,
+    /// The complete epoch transition, running every step above in order.
+    FullEpoch,
+}
+
+/// Reason an operation could not be applied to the beacon state.
+#[derive(Debug)]
+pub enum ProcessError {
+    /// The operation bytes are not a valid SSZ encoding of the expected type.
+    Decode(DecodeError),
+    /// The state transition rejected the operation.
+    Rejected(anyhow::Error),
+}
+
+impl fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessError::Decode(err) => write!(f, "failed to decode operation: {err:?}"),
+            ProcessError::Rejected(err) => write!(f, "operation rejected: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ProcessError {}
+
+/// Normalizes the return values of the state transition functions, some of which
+/// cannot fail and return `()`.
+trait IntoProcessResult {
+    fn into_process_result(self) -> Result<(), ProcessError>;
+}
+
+impl IntoProcessResult for () {
+    fn into_process_result(self) -> Result<(), ProcessError> {
+        Ok(())
+    }
+}
+
+impl IntoProcessResult for anyhow::Result<()> {
+    fn into_process_result(self) -> Result<(), ProcessError> {
+        self.map_err(ProcessError::Rejected)
+    }
+}
+
+fn decode_operation<T: ssz::Decode>(ssz_bytes: &[u8]) -> Result<T, ProcessError> {
+    from_ssz_bytes(ssz_bytes).map_err(ProcessError::Decode)
+}
+
+/// Operation input with its SSZ payload decoded, so that the operation is decoded once and
+/// the same value is both hashed and processed.
+pub enum DecodedOperation<'a> {
+    Block(DecodedBlockOperation),
+    Epoch(&'a EpochOperationWrapper),
+    Blocks(Vec<ream_consensus::electra::beacon_block::SignedBeaconBlock>),
+    Slots(&'a SlotsWrapper),
+    Rewards(&'a RewardsWrapper),
+    ForkUpgrade(&'a ForkUpgradeWrapper),
+}
+
+/// Block operation decoded from the bytes of a `BlockOperationWrapper`.
+pub enum DecodedBlockOperation {
+    Attestation(Box<ream_consensus::attestation::Attestation>),
+    AttesterSlashing(Box<ream_consensus::attester_slashing::AttesterSlashing>),
+    BlockHeader(Box<ream_consensus::electra::beacon_block::BeaconBlock>),
+    BLSToExecutionChange(Box<ream_consensus::bls_to_execution_change::SignedBLSToExecutionChange>),
+    Deposit(Box<ream_consensus::deposit::Deposit>),
+    ExecutionPayload {
+        body: Box<ream_consensus::electra::beacon_block_body::BeaconBlockBody>,
+        execution_valid: bool,
+    },
+    ProposerSlashing(Box<ream_consensus::proposer_slashing::ProposerSlashing>),
+    SyncAggregate(Box<ream_consensus::sync_aggregate::SyncAggregate>),
+    VoluntaryExit(Box<ream_consensus::voluntary_exit::SignedVoluntaryExit>),
+    Withdrawals(Box<ream_consensus::electra::execution_payload::ExecutionPayload>),
 }
 
 impl OperationInput {
-    /// Process the operation on the beacon state.
-    pub fn process(&self, state: &mut BeaconState) {
+    /// Tag of the operation variant, stored as the operation kind of a `ZiskInput`.
+    pub fn kind(&self) -> u8 {
+        match self {
+            OperationInput::Block(_) => 0,
+            OperationInput::Epoch(_) => 1,
+            OperationInput::Blocks(_) => 2,
+            OperationInput::Slots(_) => 3,
+            OperationInput::Rewards(_) => 4,
+            OperationInput::ForkUpgrade(_) => 5,
+        }
+    }
+
+    /// SSZ encoding of the wrapped operation.
+    pub fn operation_ssz_bytes(&self) -> Vec<u8> {
         match self {
-            OperationInput::Block(wrapper) => {
-                let _ = wrapper.process_operation(state);
+            OperationInput::Block(wrapper) => wrapper.as_ssz_bytes(),
+            OperationInput::Epoch(wrapper) => wrapper.as_ssz_bytes(),
+            OperationInput::Blocks(wrapper) => wrapper.as_ssz_bytes(),
+            OperationInput::Slots(wrapper) => wrapper.as_ssz_bytes(),
+            OperationInput::Rewards(_) => Vec::new(),
+            OperationInput::ForkUpgrade(wrapper) => wrapper.as_ssz_bytes(),
+        }
+    }
+
+    pub fn from_kind_and_ssz_bytes(kind: u8, ssz_bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(match kind {
+            0 => OperationInput::Block(from_ssz_bytes(ssz_bytes)?),
+            1 => OperationInput::Epoch(from_ssz_bytes(ssz_bytes)?),
+            2 => OperationInput::Blocks(from_ssz_bytes(ssz_bytes)?),
+            3 => OperationInput::Slots(from_ssz_bytes(ssz_bytes)?),
+            4 if ssz_bytes.is_empty() => OperationInput::Rewards(RewardsWrapper {}),
+            4 => {
+                return Err(DecodeError::InvalidByteLength {
+                    len: ssz_bytes.len(),
+                    expected: 0,
+                })
             }
-            OperationInput::Epoch(wrapper) => {
-                let _ = wrapper.process_operation(state);
+            5 => OperationInput::ForkUpgrade(from_ssz_bytes(ssz_bytes)?),
+            _ => {
+                return Err(DecodeError::BytesInvalid(format!(
+                    "unknown operation kind {kind}"
+                )))
             }
+        })
+    }
+
+    /// Decode the SSZ payload of the operation. Operations without one borrow their parameters.
+    pub fn decode(&self) -> Result<DecodedOperation<'_>, ProcessError> {
+        Ok(match self {
+            OperationInput::Block(wrapper) => DecodedOperation::Block(wrapper.decode()?),
+            OperationInput::Epoch(wrapper) => DecodedOperation::Epoch(wrapper),
+            OperationInput::Blocks(wrapper) => DecodedOperation::Blocks(wrapper.decode()?),
+            OperationInput::Slots(wrapper) => DecodedOperation::Slots(wrapper),
+            OperationInput::Rewards(wrapper) => DecodedOperation::Rewards(wrapper),
+            OperationInput::ForkUpgrade(wrapper) => DecodedOperation::ForkUpgrade(wrapper),
+        })
+    }
+
+    /// Decode the operation and process it on the beacon state.
+    pub fn process(&self, state: &mut ForkedBeaconState) -> Result<(), ProcessError> {
+        self.decode()?.process(state)
+    }
+
+    /// Decode the operation and compute its tree hash root.
+    pub fn operation_root(&self) -> Result<Hash256, ProcessError> {
+        Ok(self.decode()?.operation_root())
+    }
+}
+
+impl DecodedOperation<'_> {
+    /// Process the operation on the beacon state.
+    pub fn process(&self, state: &mut ForkedBeaconState) -> Result<(), ProcessError> {
+        match self {
+            DecodedOperation::Block(operation) => operation.process(state),
+            DecodedOperation::Epoch(wrapper) => wrapper.process_operation(state),
+            DecodedOperation::Blocks(blocks) => process_blocks(blocks, state),
+            DecodedOperation::Slots(wrapper) => wrapper.process_operation(state),
+            DecodedOperation::Rewards(wrapper) => wrapper.deltas_digest(state).map(|_| ()),
+            DecodedOperation::ForkUpgrade(wrapper) => wrapper.process_operation(state),
+        }
+    }
+
+    /// Tree hash root of the operation, binding the operation to the committed roots.
+    /// Operations without an SSZ payload hash their parameters, and a fork upgrade is bound
+    /// through its pre-state root alone.
+    pub fn operation_root(&self) -> Hash256 {
+        match self {
+            DecodedOperation::Block(operation) => operation.operation_root(),
+            DecodedOperation::Epoch(wrapper) => (wrapper.operation_type as u8).tree_hash_root(),
+            DecodedOperation::Blocks(blocks) => blocks_root(blocks),
+            DecodedOperation::Slots(wrapper) => wrapper.slots.tree_hash_root(),
+            DecodedOperation::Rewards(_) | DecodedOperation::ForkUpgrade(_) => Hash256::ZERO,
         }
     }
 }
 
 impl BlockOperationWrapper {
-    pub fn process_operation(&self, state: &mut BeaconState) {
-        use crate::ssz::from_ssz_bytes;
-
-        match self.operation_type {
+    /// Decode the operation bytes as the type the operation is processed with.
+    pub fn decode(&self) -> Result<DecodedBlockOperation, ProcessError> {
+        let ssz_bytes = &self.ssz_bytes;
+        Ok(match self.operation_type {
             BlockOperationType::Attestation => {
-                let op: ream_consensus::attestation::Attestation =
-                    from_ssz_bytes(&self.ssz_bytes).unwrap();
-                let _ = state.process_attestation(&op);
+                DecodedBlockOperation::Attestation(Box::new(decode_operation(ssz_bytes)?))
             }
             BlockOperationType::AttesterSlashing => {
-                let op: ream_consensus::attester_slashing::AttesterSlashing =
-                    from_ssz_bytes(&self.ssz_bytes).unwrap();
-                let _ = state.process_attester_slashing(&op);
+                DecodedBlockOperation::AttesterSlashing(Box::new(decode_operation(ssz_bytes)?))
             }
             BlockOperationType::BlockHeader => {
-                let op: ream_consensus::electra::beacon_block::BeaconBlock =
-                    from_ssz_bytes(&self.ssz_bytes).unwrap();
-                let _ = state.process_block_header(&op);
+                DecodedBlockOperation::BlockHeader(Box::new(decode_operation(ssz_bytes)?))
             }
             BlockOperationType::BLSToExecutionChange => {
-                let op: ream_consensus::bls_to_execution_change::SignedBLSToExecutionChange =
-                    from_ssz_bytes(&self.ssz_bytes).unwrap();
-                let _ = state.process_bls_to_execution_change(&op);
+                DecodedBlockOperation::BLSToExecutionChange(Box::new(decode_operation(ssz_bytes)?))
             }
             BlockOperationType::Deposit => {
-                let op: ream_consensus::deposit::Deposit = from_ssz_bytes(&self.ssz_bytes).unwrap();
-                let _ = state.process_deposit(&op);
-            }
-            BlockOperationType::ExecutionPayload => {
-                panic!("Not implemented");
+                DecodedBlockOperation::Deposit(Box::new(decode_operation(ssz_bytes)?))
             }
+            BlockOperationType::ExecutionPayload => DecodedBlockOperation::ExecutionPayload {
+                body: Box::new(decode_operation(ssz_bytes)?),
+                execution_valid: self.execution_valid,
+            },
             BlockOperationType::ProposerSlashing => {
-                let op: ream_consensus::proposer_slashing::ProposerSlashing =
-                    from_ssz_bytes(&self.ssz_bytes).unwrap();
-                let _ = state.process_proposer_slashing(&op);
+                DecodedBlockOperation::ProposerSlashing(Box::new(decode_operation(ssz_bytes)?))
             }
             BlockOperationType::SyncAggregate => {
-                let op: ream_consensus::sync_aggregate::SyncAggregate =
-                    from_ssz_bytes(&self.ssz_bytes).unwrap();
-                let _ = state.process_sync_aggregate(&op);
+                DecodedBlockOperation::SyncAggregate(Box::new(decode_operation(ssz_bytes)?))
             }
             BlockOperationType::VoluntaryExit => {
-                let op: ream_consensus::voluntary_exit::SignedVoluntaryExit =
-                    from_ssz_bytes(&self.ssz_bytes).unwrap();
-                let _ = state.process_voluntary_exit(&op);
+                DecodedBlockOperation::VoluntaryExit(Box::new(decode_operation(ssz_bytes)?))
             }
             BlockOperationType::Withdrawals => {
-                let op: ream_consensus::electra::execution_payload::ExecutionPayload =
-                    from_ssz_bytes(&self.ssz_bytes).unwrap();
-                let _ = state.process_withdrawals(&op);
+                DecodedBlockOperation::Withdrawals(Box::new(decode_operation(ssz_bytes)?))
+            }
+        })
+    }
+}
+
+impl DecodedBlockOperation {
+    pub fn operation_root(&self) -> Hash256 {
+        match self {
+            DecodedBlockOperation::Attestation(op) => op.tree_hash_root(),
+            DecodedBlockOperation::AttesterSlashing(op) => op.tree_hash_root(),
+            DecodedBlockOperation::BlockHeader(op) => op.tree_hash_root(),
+            DecodedBlockOperation::BLSToExecutionChange(op) => op.tree_hash_root(),
+            DecodedBlockOperation::Deposit(op) => op.tree_hash_root(),
+            DecodedBlockOperation::ExecutionPayload { body, .. } => body.tree_hash_root(),
+            DecodedBlockOperation::ProposerSlashing(op) => op.tree_hash_root(),
+            DecodedBlockOperation::SyncAggregate(op) => op.tree_hash_root(),
+            DecodedBlockOperation::VoluntaryExit(op) => op.tree_hash_root(),
+            DecodedBlockOperation::Withdrawals(op) => op.tree_hash_root(),
+        }
+    }
+
+    pub fn process(&self, state: &mut ForkedBeaconState) -> Result<(), ProcessError> {
+        match self {
+            DecodedBlockOperation::Attestation(op) => {
+                for_each_fork!(state, state => state.process_attestation(op)).into_process_result()
+            }
+            DecodedBlockOperation::AttesterSlashing(op) => {
+                for_each_fork!(state, state => state.process_attester_slashing(op))
+                    .into_process_result()
+            }
+            DecodedBlockOperation::BlockHeader(op) => {
+                for_each_fork!(state, state => state.process_block_header(op)).into_process_result()
+            }
+            DecodedBlockOperation::BLSToExecutionChange(op) => {
+                for_each_fork!(state, state => state.process_bls_to_execution_change(op))
+                    .into_process_result()
+            }
+            DecodedBlockOperation::Deposit(op) => {
+                for_each_fork!(state, state => state.process_deposit(op)).into_process_result()
+            }
+            DecodedBlockOperation::ExecutionPayload {
+                body,
+                execution_valid,
+            } => {
+                let execution_engine = MockExecutionEngine::new(*execution_valid);
+                for_each_fork!(state, state => {
+                    state.process_execution_payload(body, &execution_engine)
+                })
+                .into_process_result()
+            }
+            DecodedBlockOperation::ProposerSlashing(op) => {
+                for_each_fork!(state, state => state.process_proposer_slashing(op))
+                    .into_process_result()
+            }
+            DecodedBlockOperation::SyncAggregate(op) => {
+                for_each_fork!(state, state => state.process_sync_aggregate(op))
+                    .into_process_result()
+            }
+            DecodedBlockOperation::VoluntaryExit(op) => {
+                for_each_fork!(state, state => state.process_voluntary_exit(op))
+                    .into_process_result()
+            }
+            DecodedBlockOperation::Withdrawals(op) => {
+                for_each_fork!(state, state => state.process_withdrawals(op)).into_process_result()
             }
         }
     }
 }
 
 impl EpochOperationWrapper {
-    pub fn process_operation(&self, state: &mut BeaconState) {
+    pub fn process_operation(&self, state: &mut ForkedBeaconState) -> Result<(), ProcessError> {
         match self.operation_type {
             EpochOperationType::JustificationAndFinalization => {
-                let _ = state.process_justification_and_finalization();
+                for_each_fork!(state, state => state.process_justification_and_finalization())
+                    .into_process_result()
             }
             EpochOperationType::InactivityUpdates => {
-                let _ = state.process_inactivity_updates();
+                for_each_fork!(state, state => state.process_inactivity_updates())
+                    .into_process_result()
             }
             EpochOperationType::RewardsAndPenalties => {
-                let _ = state.process_rewards_and_penalties();
+                for_each_fork!(state, state => state.process_rewards_and_penalties())
+                    .into_process_result()
             }
             EpochOperationType::RegistryUpdates => {
-                let _ = state.process_registry_updates();
+                for_each_fork!(state, state => state.process_registry_updates())
+                    .into_process_result()
             }
             EpochOperationType::Slashings => {
-                let _ = state.process_slashings();
+                for_each_fork!(state, state => state.process_slashings()).into_process_result()
             }
             EpochOperationType::Eth1DataReset => {
-                let _ = state.process_eth1_data_reset();
+                for_each_fork!(state, state => state.process_eth1_data_reset())
+                    .into_process_result()
             }
             EpochOperationType::PendingDeposits => {
-                let _ = state.process_pending_deposits();
+                for_each_fork!(state, state => state.process_pending_deposits())
+                    .into_process_result()
             }
             EpochOperationType::PendingConsolidations => {
-                let _ = state.process_pending_consolidations();
+                for_each_fork!(state, state => state.process_pending_consolidations())
+                    .into_process_result()
             }
             EpochOperationType::EffectiveBalanceUpdates => {
-                let _ = state.process_effective_balance_updates();
+                for_each_fork!(state, state => state.process_effective_balance_updates())
+                    .into_process_result()
             }
             EpochOperationType::SlashingsReset => {
-                let _ = state.process_slashings_reset();
+                for_each_fork!(state, state => state.process_slashings_reset())
+                    .into_process_result()
             }
             EpochOperationType::RandaoMixesReset => {
-                let _ = state.process_randao_mixes_reset();
+                for_each_fork!(state, state => state.process_randao_mixes_reset())
+                    .into_process_result()
             }
             EpochOperationType::HistoricalSummariesUpdate => {
-                let _ = state.process_historical_summaries_update();
+                for_each_fork!(state, state => state.process_historical_summaries_update())
+                    .into_process_result()
             }
             EpochOperationType::ParticipationFlagUpdates => {
-                let _ = state.process_participation_flag_updates();
+                for_each_fork!(state, state => state.process_participation_flag_updates())
+                    .into_process_result()
+            }
+            EpochOperationType::SyncCommitteeUpdates => {
+                for_each_fork!(state, state => state.process_sync_committee_updates())
+                    .into_process_result()
+            }
+            EpochOperationType::ProposerLookahead => match state {
+                ForkedBeaconState::Fulu(state) => {
+                    state.process_proposer_lookahead().into_process_result()
+                }
+                ForkedBeaconState::Electra(_) => Err(ProcessError::Rejected(anyhow::anyhow!(
+                    "the proposer lookahead is only processed from Fulu on"
+                ))),
+            },
+            EpochOperationType::FullEpoch => {
+                for_each_fork!(state, state => state.process_epoch()).into_process_result()
             }
         }
     }
 }
+
+impl BlocksWrapper {
+    pub fn decode(
+        &self,
+    ) -> Result<Vec<ream_consensus::electra::beacon_block::SignedBeaconBlock>, ProcessError> {
+        self.blocks
+            .iter()
+            .map(|ssz_bytes| decode_operation(ssz_bytes))
+            .collect()
+    }
+}
+
+/// Root of the list of signed blocks, as if they were an SSZ list.
+fn blocks_root(blocks: &[ream_consensus::electra::beacon_block::SignedBeaconBlock]) -> Hash256 {
+    let mut block_roots = Vec::with_capacity(blocks.len() * 32);
+    for block in blocks {
+        block_roots.extend_from_slice(block.tree_hash_root().as_slice());
+    }
+    tree_hash::mix_in_length(&tree_hash::merkle_root(&block_roots, 0), blocks.len())
+}
+
+/// Run the full state transition, including signature verification, for every block.
+fn process_blocks(
+    blocks: &[ream_consensus::electra::beacon_block::SignedBeaconBlock],
+    state: &mut ForkedBeaconState,
+) -> Result<(), ProcessError> {
+    let execution_engine = MockExecutionEngine::new(true);
+    for block in blocks {
+        for_each_fork!(state, state => {
+            state.state_transition(block, true, &execution_engine)
+        })
+        .into_process_result()?;
+    }
+    Ok(())
+}
+
+impl SlotsWrapper {
+    /// Advance the state through empty slots, crossing any epoch boundary on the way.
+    pub fn process_operation(&self, state: &mut ForkedBeaconState) -> Result<(), ProcessError> {
+        for_each_fork!(state, state => {
+            let target_slot = state.slot + self.slots;
+            state.process_slots(target_slot)
+        })
+        .into_process_result()
+    }
+}
+
+impl RewardsWrapper {
+    /// Compute the attestation deltas of the state and digest their SSZ encodings.
+    pub fn deltas_digest(&self, state: &ForkedBeaconState) -> Result<B256, ProcessError> {
+        let deltas = attestation_deltas(state).map_err(ProcessError::Rejected)?;
+        let encoded_deltas = deltas.map(|deltas| ssz::Encode::as_ssz_bytes(&deltas));
+        Ok(deltas_digest(&encoded_deltas))
+    }
+}
+
+impl ForkUpgradeWrapper {
+    /// Decode the previous-fork state that upgrades to `fork`.
+    pub fn decode_pre_state(&self, fork: ForkType) -> Result<PreForkBeaconState, ProcessError> {
+        PreForkBeaconState::from_ssz_bytes(fork, &self.pre_state_ssz_bytes)
+            .map_err(ProcessError::Decode)
+    }
+
+    /// Replace the state with the upgraded previous-fork state. Only the fork of the
+    /// given state is used.
+    pub fn process_operation(&self, state: &mut ForkedBeaconState) -> Result<(), ProcessError> {
+        let pre_state = self.decode_pre_state(state.fork())?;
+        *state = pre_state.upgrade().map_err(ProcessError::Rejected)?;
+        Ok(())
+    }
+}
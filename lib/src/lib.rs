@@ -1,4 +1,13 @@
+pub mod checkpoint;
+pub mod execute;
 pub mod file;
+pub mod fork;
 pub mod input;
+pub mod outcome;
+pub mod phase;
+pub mod preset;
+pub mod protocol;
+pub mod rewards;
 pub mod snappy;
 pub mod ssz;
+pub mod state;
@@ -0,0 +1,53 @@
+use std::fmt;
+
+use crate::input::ProcessError;
+
+/// Outcome of applying an operation to the pre-state, as committed by the guest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The operation was applied successfully.
+    Success,
+    /// The state transition rejected the operation.
+    Rejected,
+    /// The operation bytes could not be decoded.
+    InvalidInput,
+}
+
+impl Outcome {
+    pub fn code(&self) -> u32 {
+        match self {
+            Outcome::Success => 0,
+            Outcome::Rejected => 1,
+            Outcome::InvalidInput => 2,
+        }
+    }
+
+    pub fn from_code(code: u32) -> Option<Self> {
+        match code {
+            0 => Some(Outcome::Success),
+            1 => Some(Outcome::Rejected),
+            2 => Some(Outcome::InvalidInput),
+            _ => None,
+        }
+    }
+}
+
+impl From<&Result<(), ProcessError>> for Outcome {
+    fn from(result: &Result<(), ProcessError>) -> Self {
+        match result {
+            Ok(()) => Outcome::Success,
+            Err(ProcessError::Rejected(_)) => Outcome::Rejected,
+            Err(ProcessError::Decode(_)) => Outcome::InvalidInput,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Success => f.write_str("success"),
+            Outcome::Rejected => f.write_str("rejected"),
+            Outcome::InvalidInput => f.write_str("invalid input"),
+        }
+    }
+}
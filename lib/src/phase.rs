@@ -0,0 +1,102 @@
+use std::fmt;
+
+/// Prefix of the marker line the guest prints after each measured phase.
+pub const PHASE_MARKER: &str = "PHASE_STEPS";
+
+/// Guest execution phases whose step counts are reported to the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    DeserializePreState,
+    DeserializeOperation,
+    /// Tree-hashing the pre-state and the operation for their committed roots.
+    MerkleizeInputs,
+    Process,
+    /// Tree-hashing the processed state.
+    Merkleize,
+    Output,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 6] = [
+        Phase::DeserializePreState,
+        Phase::DeserializeOperation,
+        Phase::MerkleizeInputs,
+        Phase::Process,
+        Phase::Merkleize,
+        Phase::Output,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::DeserializePreState => "deserialize-pre-state-ssz",
+            Phase::DeserializeOperation => "deserialize-operation-input",
+            Phase::MerkleizeInputs => "merkleize-inputs",
+            Phase::Process => "process-operation",
+            Phase::Merkleize => "merkleize-operation",
+            Phase::Output => "output-state-root",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|phase| phase.name() == name)
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Step counts accumulated per phase. A phase that is entered more than once
+/// reports the sum of all its runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PhaseSteps {
+    steps: [u64; Phase::ALL.len()],
+}
+
+impl PhaseSteps {
+    pub fn add(&mut self, phase: Phase, steps: u64) {
+        self.steps[phase.index()] += steps;
+    }
+
+    pub fn get(&self, phase: Phase) -> u64 {
+        self.steps[phase.index()]
+    }
+
+    pub fn total(&self) -> u64 {
+        self.steps.iter().sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Phase, u64)> + '_ {
+        Phase::ALL.into_iter().map(|phase| (phase, self.get(phase)))
+    }
+
+    /// Collect every `PHASE_STEPS:<phase>:<steps>` marker line found in the guest output.
+    pub fn parse(output: &str) -> Self {
+        let mut phase_steps = Self::default();
+
+        for line in output.lines() {
+            let mut parts = line.trim().splitn(3, ':');
+            if parts.next() != Some(PHASE_MARKER) {
+                continue;
+            }
+            let phase = parts.next().and_then(Phase::from_name);
+            let steps = parts.next().and_then(|steps| steps.parse::<u64>().ok());
+            if let (Some(phase), Some(steps)) = (phase, steps) {
+                phase_steps.add(phase, steps);
+            }
+        }
+
+        phase_steps
+    }
+}
+
+/// Format the marker line the host looks for in the guest output.
+pub fn format_phase_steps(phase: Phase, steps: u64) -> String {
+    format!("{PHASE_MARKER}:{phase}:{steps}")
+}
@@ -0,0 +1,31 @@
+use std::fmt;
+
+/// Consensus preset the beacon state types are compiled for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Mainnet,
+    Minimal,
+}
+
+impl Preset {
+    /// Preset selected at build time through the `minimal` feature.
+    pub const COMPILED: Preset = if cfg!(feature = "minimal") {
+        Preset::Minimal
+    } else {
+        Preset::Mainnet
+    };
+
+    /// Name of the preset, which is also the directory of its spec test vectors.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Mainnet => "mainnet",
+            Preset::Minimal => "minimal",
+        }
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,388 @@
+use alloy_primitives::B256;
+use ssz::{DecodeError, Encode, BYTES_PER_LENGTH_OFFSET};
+use ssz_derive::{Decode, Encode};
+
+use crate::{
+    checkpoint::FinalizedCheckpoint, fork::ForkType, input::OperationInput, outcome::Outcome,
+};
+
+/// Version of the host-guest protocol, bumped whenever the input or output layout changes.
+pub const PROTOCOL_VERSION: u8 = 5;
+
+/// Number of 32-bit words a root takes up in the public outputs.
+const ROOT_WORDS: usize = 8;
+
+/// Number of roots in the public outputs: the pre-state, operation and post-state roots, the
+/// deltas digest and the finalized checkpoint root.
+const ROOTS: usize = 5;
+
+/// First public output word, marking where the outputs start in the emulator output.
+const PUBLIC_OUTPUTS_HEADER: u32 = 0x636f_6e73;
+
+/// Guest input, encoded as a single SSZ container so the guest decodes it in one pass.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct ZiskInput {
+    pub version: u8,
+    pub fork: u8,
+    pub operation_kind: u8,
+    pub pre_state_ssz_bytes: Vec<u8>,
+    /// SSZ encoding of the operation wrapper selected by `operation_kind`.
+    pub operation_ssz_bytes: Vec<u8>,
+}
+
+impl ZiskInput {
+    pub fn new(fork: ForkType, pre_state_ssz_bytes: Vec<u8>, operation: &OperationInput) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            fork: fork.tag(),
+            operation_kind: operation.kind(),
+            pre_state_ssz_bytes,
+            operation_ssz_bytes: operation.operation_ssz_bytes(),
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    /// Decode the container, rejecting inputs written for another protocol version.
+    pub fn decode(ssz_bytes: &[u8]) -> Result<Self, DecodeError> {
+        let input = ZiskInputRef::decode(ssz_bytes)?;
+        Ok(Self {
+            version: PROTOCOL_VERSION,
+            fork: input.fork,
+            operation_kind: input.operation_kind,
+            pre_state_ssz_bytes: input.pre_state_ssz_bytes.to_vec(),
+            operation_ssz_bytes: input.operation_ssz_bytes.to_vec(),
+        })
+    }
+
+    pub fn fork(&self) -> Result<ForkType, DecodeError> {
+        fork_from_tag(self.fork)
+    }
+
+    pub fn operation(&self) -> Result<OperationInput, DecodeError> {
+        OperationInput::from_kind_and_ssz_bytes(self.operation_kind, &self.operation_ssz_bytes)
+    }
+}
+
+/// Encoded `ZiskInput` whose byte fields borrow from the input buffer, so unwrapping the
+/// container copies neither the pre-state nor the operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZiskInputRef<'a> {
+    pub fork: u8,
+    pub operation_kind: u8,
+    pub pre_state_ssz_bytes: &'a [u8],
+    pub operation_ssz_bytes: &'a [u8],
+}
+
+impl<'a> ZiskInputRef<'a> {
+    /// Length of the fixed part of the container: the three tags and the offsets of the two
+    /// byte fields.
+    const FIXED_LEN: usize = 3 + 2 * BYTES_PER_LENGTH_OFFSET;
+
+    /// Split the container into its fields, rejecting inputs written for another protocol
+    /// version.
+    pub fn decode(ssz_bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        if ssz_bytes.len() < Self::FIXED_LEN {
+            return Err(DecodeError::InvalidByteLength {
+                len: ssz_bytes.len(),
+                expected: Self::FIXED_LEN,
+            });
+        }
+        if ssz_bytes[0] != PROTOCOL_VERSION {
+            return Err(DecodeError::BytesInvalid(format!(
+                "unsupported protocol version {}, expected {PROTOCOL_VERSION}",
+                ssz_bytes[0]
+            )));
+        }
+        let offset = |i: usize| {
+            let start = 3 + i * BYTES_PER_LENGTH_OFFSET;
+            let bytes = &ssz_bytes[start..start + BYTES_PER_LENGTH_OFFSET];
+            u32::from_le_bytes(bytes.try_into().expect("offset has offset length")) as usize
+        };
+        let (operation_start, operation_end) = (offset(1), ssz_bytes.len());
+        if offset(0) != Self::FIXED_LEN
+            || operation_start < Self::FIXED_LEN
+            || operation_start > operation_end
+        {
+            return Err(DecodeError::BytesInvalid(format!(
+                "invalid input offsets {} and {operation_start}",
+                offset(0)
+            )));
+        }
+        Ok(Self {
+            fork: ssz_bytes[1],
+            operation_kind: ssz_bytes[2],
+            pre_state_ssz_bytes: &ssz_bytes[Self::FIXED_LEN..operation_start],
+            operation_ssz_bytes: &ssz_bytes[operation_start..operation_end],
+        })
+    }
+
+    pub fn fork(&self) -> Result<ForkType, DecodeError> {
+        fork_from_tag(self.fork)
+    }
+
+    pub fn operation(&self) -> Result<OperationInput, DecodeError> {
+        OperationInput::from_kind_and_ssz_bytes(self.operation_kind, self.operation_ssz_bytes)
+    }
+}
+
+fn fork_from_tag(tag: u8) -> Result<ForkType, DecodeError> {
+    ForkType::from_tag(tag).ok_or_else(|| DecodeError::BytesInvalid(format!("unknown fork {tag}")))
+}
+
+/// Values the guest commits through the zkVM public outputs, which a proof binds to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicOutputs {
+    pub pre_state_root: B256,
+    pub operation_root: B256,
+    pub post_state_root: B256,
+    /// Digest of the computed deltas for rewards operations, zero for every other operation.
+    pub deltas_digest: B256,
+    /// Finalized checkpoint of the post-state.
+    pub finalized_checkpoint: FinalizedCheckpoint,
+    pub outcome: Outcome,
+}
+
+impl PublicOutputs {
+    /// Number of 32-bit public output words: the header, the protocol version, the roots,
+    /// the finalized checkpoint epoch as two words and the outcome code.
+    pub const WORDS: usize = ROOTS * ROOT_WORDS + 5;
+
+    /// Index of the high word of the finalized checkpoint epoch, followed by the low word.
+    const EPOCH_WORD: usize = 2 + ROOTS * ROOT_WORDS;
+
+    /// Encode the outputs as public output words. Roots are split into big-endian words,
+    /// so the hex form of the words reads as the hex form of the root.
+    pub fn to_words(&self) -> [u32; Self::WORDS] {
+        let mut words = [0u32; Self::WORDS];
+        words[0] = PUBLIC_OUTPUTS_HEADER;
+        words[1] = PROTOCOL_VERSION as u32;
+        let roots = [
+            self.pre_state_root,
+            self.operation_root,
+            self.post_state_root,
+            self.deltas_digest,
+            self.finalized_checkpoint.root,
+        ];
+        for (i, root) in roots.iter().enumerate() {
+            for (j, chunk) in root.as_slice().chunks(4).enumerate() {
+                words[2 + i * ROOT_WORDS + j] =
+                    u32::from_be_bytes(chunk.try_into().expect("chunk has word length"));
+            }
+        }
+        let epoch = self.finalized_checkpoint.epoch;
+        words[Self::EPOCH_WORD] = (epoch >> 32) as u32;
+        words[Self::EPOCH_WORD + 1] = epoch as u32;
+        words[Self::WORDS - 1] = self.outcome.code();
+        words
+    }
+
+    /// Decode the public output words, rejecting outputs of another protocol version.
+    pub fn from_words(words: &[u32]) -> Option<Self> {
+        if words.len() != Self::WORDS
+            || words[0] != PUBLIC_OUTPUTS_HEADER
+            || words[1] != PROTOCOL_VERSION as u32
+        {
+            return None;
+        }
+        let root = |i: usize| {
+            let bytes: Vec<u8> = words[2 + i * ROOT_WORDS..2 + (i + 1) * ROOT_WORDS]
+                .iter()
+                .flat_map(|word| word.to_be_bytes())
+                .collect();
+            B256::from_slice(&bytes)
+        };
+        Some(Self {
+            pre_state_root: root(0),
+            operation_root: root(1),
+            post_state_root: root(2),
+            deltas_digest: root(3),
+            finalized_checkpoint: FinalizedCheckpoint {
+                epoch: ((words[Self::EPOCH_WORD] as u64) << 32)
+                    | words[Self::EPOCH_WORD + 1] as u64,
+                root: root(4),
+            },
+            outcome: Outcome::from_code(words[Self::WORDS - 1])?,
+        })
+    }
+
+    /// Read the public output words from the emulator output, which prints each word as
+    /// an 8-digit hex line. The words are the `WORDS` consecutive lines starting at the
+    /// header line, and an output holding no frame or more than one is rejected.
+    pub fn parse(output: &str) -> Option<Self> {
+        let lines: Vec<&str> = output.lines().map(str::trim).collect();
+        let header = format!("{PUBLIC_OUTPUTS_HEADER:08x}");
+        let start = lines.iter().position(|line| *line == header)?;
+        let end = start + Self::WORDS;
+        if lines.get(end..)?.contains(&header.as_str()) {
+            return None;
+        }
+        let words: Vec<u32> = lines[start..end]
+            .iter()
+            .map(|line| parse_word(line))
+            .collect::<Option<_>>()?;
+        Self::from_words(&words)
+    }
+}
+
+/// Parse an 8-digit hex public output word.
+fn parse_word(line: &str) -> Option<u32> {
+    if line.len() != 8 || !line.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(line, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{
+        BlockOperationType, BlockOperationWrapper, EpochOperationType, EpochOperationWrapper,
+        SlotsWrapper,
+    };
+
+    fn round_trip(operation: OperationInput) -> OperationInput {
+        let input = ZiskInput::new(ForkType::Fulu, vec![1, 2, 3], &operation);
+        let decoded = ZiskInput::decode(&input.encode()).unwrap();
+
+        assert_eq!(decoded, input);
+        assert_eq!(decoded.fork().unwrap(), ForkType::Fulu);
+        assert_eq!(decoded.pre_state_ssz_bytes, vec![1, 2, 3]);
+        decoded.operation().unwrap()
+    }
+
+    #[test]
+    fn block_operation_round_trips() {
+        let operation = round_trip(OperationInput::Block(BlockOperationWrapper {
+            operation_type: BlockOperationType::ExecutionPayload,
+            ssz_bytes: vec![4, 5, 6],
+            execution_valid: false,
+        }));
+
+        let OperationInput::Block(wrapper) = operation else {
+            panic!("expected a block operation, got {operation:?}");
+        };
+        assert!(matches!(
+            wrapper.operation_type,
+            BlockOperationType::ExecutionPayload
+        ));
+        assert_eq!(wrapper.ssz_bytes, vec![4, 5, 6]);
+        assert!(!wrapper.execution_valid);
+    }
+
+    #[test]
+    fn epoch_and_slots_operations_round_trip() {
+        let operation = round_trip(OperationInput::Epoch(EpochOperationWrapper {
+            operation_type: EpochOperationType::FullEpoch,
+        }));
+        assert!(matches!(
+            operation,
+            OperationInput::Epoch(EpochOperationWrapper {
+                operation_type: EpochOperationType::FullEpoch
+            })
+        ));
+
+        let operation = round_trip(OperationInput::Slots(SlotsWrapper { slots: 42 }));
+        assert!(matches!(
+            operation,
+            OperationInput::Slots(SlotsWrapper { slots: 42 })
+        ));
+    }
+
+    #[test]
+    fn decode_rejects_other_versions() {
+        let mut input = ZiskInput::new(
+            ForkType::Electra,
+            Vec::new(),
+            &OperationInput::Slots(SlotsWrapper { slots: 1 }),
+        );
+        input.version = PROTOCOL_VERSION + 1;
+
+        assert!(ZiskInput::decode(&input.encode()).is_err());
+    }
+
+    #[test]
+    fn borrowed_decode_matches_the_container_encoding() {
+        let input = ZiskInput::new(
+            ForkType::Fulu,
+            vec![1, 2, 3],
+            &OperationInput::Slots(SlotsWrapper { slots: 42 }),
+        );
+        let bytes = input.encode();
+        let decoded = ZiskInputRef::decode(&bytes).unwrap();
+
+        let expected = <ZiskInput as ssz::Decode>::from_ssz_bytes(&bytes).unwrap();
+        assert_eq!(decoded.fork, expected.fork);
+        assert_eq!(decoded.operation_kind, expected.operation_kind);
+        assert_eq!(decoded.pre_state_ssz_bytes, expected.pre_state_ssz_bytes);
+        assert_eq!(decoded.operation_ssz_bytes, expected.operation_ssz_bytes);
+
+        // Offsets pointing before the byte fields or past the end are rejected
+        let mut bad_offset = bytes.clone();
+        bad_offset[7..11].copy_from_slice(&(bytes.len() as u32 + 1).to_le_bytes());
+        assert!(ZiskInputRef::decode(&bad_offset).is_err());
+        bad_offset[3..7].copy_from_slice(&0u32.to_le_bytes());
+        assert!(ZiskInputRef::decode(&bad_offset).is_err());
+        assert!(ZiskInputRef::decode(&bytes[..10]).is_err());
+    }
+
+    fn public_outputs() -> PublicOutputs {
+        PublicOutputs {
+            pre_state_root: B256::repeat_byte(0x11),
+            operation_root: B256::repeat_byte(0x22),
+            post_state_root: B256::repeat_byte(0x33),
+            deltas_digest: B256::repeat_byte(0x44),
+            finalized_checkpoint: FinalizedCheckpoint {
+                epoch: 0x1_0000_0002,
+                root: B256::repeat_byte(0x55),
+            },
+            outcome: Outcome::Rejected,
+        }
+    }
+
+    fn output_words(outputs: &PublicOutputs) -> Vec<String> {
+        outputs
+            .to_words()
+            .iter()
+            .map(|word| format!("{word:08x}"))
+            .collect()
+    }
+
+    #[test]
+    fn public_outputs_round_trip_through_emulator_output() {
+        let outputs = public_outputs();
+        // Marker lines that happen to look like words do not shift the frame
+        let output = ["PHASE_STEPS:process-operation:100", "deadbeef"]
+            .into_iter()
+            .map(str::to_string)
+            .chain(output_words(&outputs))
+            .chain(["process_rom() steps=100".to_string()])
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        assert_eq!(
+            PublicOutputs::from_words(&outputs.to_words()),
+            Some(outputs)
+        );
+        assert_eq!(PublicOutputs::parse(&output), Some(outputs));
+    }
+
+    #[test]
+    fn parse_rejects_truncated_or_repeated_frames() {
+        let words = output_words(&public_outputs());
+
+        let truncated = words[..PublicOutputs::WORDS - 1].join("\n");
+        assert_eq!(PublicOutputs::parse(&truncated), None);
+
+        let mut interrupted = words.clone();
+        interrupted.insert(3, "PHASE_STEPS:output-state-root:1".to_string());
+        assert_eq!(PublicOutputs::parse(&interrupted.join("\n")), None);
+
+        let repeated = [words.clone(), words].concat().join("\n");
+        assert_eq!(PublicOutputs::parse(&repeated), None);
+
+        assert_eq!(PublicOutputs::parse("00000003\n00000001"), None);
+    }
+}
@@ -0,0 +1,79 @@
+use alloy_primitives::B256;
+use sha2::{Digest, Sha256};
+use ssz_derive::{Decode, Encode};
+
+use crate::state::{for_each_fork, ForkedBeaconState};
+
+/// Participation flag indices, as used by `get_flag_index_deltas`.
+pub const TIMELY_SOURCE_FLAG_INDEX: u8 = 0;
+pub const TIMELY_TARGET_FLAG_INDEX: u8 = 1;
+pub const TIMELY_HEAD_FLAG_INDEX: u8 = 2;
+
+/// Spec test files holding the expected deltas, in the order they are digested.
+pub const DELTAS_FILES: [&str; 4] = [
+    "source_deltas",
+    "target_deltas",
+    "head_deltas",
+    "inactivity_penalty_deltas",
+];
+
+/// Per-validator rewards and penalties, matching the `Deltas` container of the spec tests.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct Deltas {
+    pub rewards: Vec<u64>,
+    pub penalties: Vec<u64>,
+}
+
+impl From<(Vec<u64>, Vec<u64>)> for Deltas {
+    fn from((rewards, penalties): (Vec<u64>, Vec<u64>)) -> Self {
+        Self { rewards, penalties }
+    }
+}
+
+/// SHA-256 over the SSZ encodings of the deltas, in `DELTAS_FILES` order. Each encoding is
+/// prefixed with its little-endian `u64` length, so that no two sequences of deltas hash the
+/// same input.
+pub fn deltas_digest<T: AsRef<[u8]>>(encoded_deltas: &[T]) -> B256 {
+    let mut hasher = Sha256::new();
+    for encoded in encoded_deltas {
+        let encoded = encoded.as_ref();
+        hasher.update((encoded.len() as u64).to_le_bytes());
+        hasher.update(encoded);
+    }
+    B256::from_slice(&hasher.finalize())
+}
+
+/// Attestation deltas of the state, in `DELTAS_FILES` order.
+pub fn attestation_deltas(state: &ForkedBeaconState) -> anyhow::Result<[Deltas; 4]> {
+    for_each_fork!(state, state => Ok([
+        state.get_flag_index_deltas(TIMELY_SOURCE_FLAG_INDEX)?.into(),
+        state.get_flag_index_deltas(TIMELY_TARGET_FLAG_INDEX)?.into(),
+        state.get_flag_index_deltas(TIMELY_HEAD_FLAG_INDEX)?.into(),
+        state.get_inactivity_penalty_deltas()?.into(),
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use ssz::Encode;
+
+    use super::*;
+
+    #[test]
+    fn deltas_digest_distinguishes_deltas_with_the_same_concatenated_encoding() {
+        // The penalty reads as the two offsets of an empty `Deltas` encoding
+        let penalty = 0x0000_0008_0000_0008;
+        let empty = Deltas::from((vec![], vec![])).as_ssz_bytes();
+        let one_penalty = Deltas::from((vec![], vec![penalty])).as_ssz_bytes();
+        let first = [
+            one_penalty.clone(),
+            empty.clone(),
+            empty.clone(),
+            empty.clone(),
+        ];
+        let second = [empty.clone(), one_penalty, empty.clone(), empty];
+        assert_eq!(first.concat(), second.concat());
+
+        assert_ne!(deltas_digest(&first), deltas_digest(&second));
+    }
+}
@@ -0,0 +1,86 @@
+use ream_consensus::{deneb, electra, fulu};
+use ssz::DecodeError;
+use tree_hash::{Hash256, TreeHash};
+
+use crate::{checkpoint::FinalizedCheckpoint, fork::ForkType, ssz::from_ssz_bytes};
+
+/// Beacon state of any supported fork.
+#[derive(Debug)]
+pub enum ForkedBeaconState {
+    Electra(Box<electra::beacon_state::BeaconState>),
+    Fulu(Box<fulu::beacon_state::BeaconState>),
+}
+
+/// Evaluate `$body` with `$state` bound to the fork-specific beacon state.
+macro_rules! for_each_fork {
+    ($forked_state:expr, $state:ident => $body:expr) => {
+        match $forked_state {
+            $crate::state::ForkedBeaconState::Electra($state) => $body,
+            $crate::state::ForkedBeaconState::Fulu($state) => $body,
+        }
+    };
+}
+pub(crate) use for_each_fork;
+
+impl ForkedBeaconState {
+    pub fn from_ssz_bytes(fork: ForkType, ssz_bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(match fork {
+            ForkType::Electra => ForkedBeaconState::Electra(Box::new(from_ssz_bytes(ssz_bytes)?)),
+            ForkType::Fulu => ForkedBeaconState::Fulu(Box::new(from_ssz_bytes(ssz_bytes)?)),
+        })
+    }
+
+    pub fn fork(&self) -> ForkType {
+        match self {
+            ForkedBeaconState::Electra(_) => ForkType::Electra,
+            ForkedBeaconState::Fulu(_) => ForkType::Fulu,
+        }
+    }
+
+    pub fn tree_hash_root(&self) -> Hash256 {
+        for_each_fork!(self, state => state.tree_hash_root())
+    }
+
+    pub fn finalized_checkpoint(&self) -> FinalizedCheckpoint {
+        for_each_fork!(self, state => FinalizedCheckpoint {
+            epoch: state.finalized_checkpoint.epoch,
+            root: state.finalized_checkpoint.root,
+        })
+    }
+}
+
+/// Beacon state of the fork preceding a supported fork, as consumed by the fork upgrade.
+#[derive(Debug)]
+pub enum PreForkBeaconState {
+    Deneb(Box<deneb::beacon_state::BeaconState>),
+    Electra(Box<electra::beacon_state::BeaconState>),
+}
+
+impl PreForkBeaconState {
+    /// Decode the state of the fork that `fork` upgrades from.
+    pub fn from_ssz_bytes(fork: ForkType, ssz_bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(match fork {
+            ForkType::Electra => PreForkBeaconState::Deneb(Box::new(from_ssz_bytes(ssz_bytes)?)),
+            ForkType::Fulu => PreForkBeaconState::Electra(Box::new(from_ssz_bytes(ssz_bytes)?)),
+        })
+    }
+
+    pub fn tree_hash_root(&self) -> Hash256 {
+        match self {
+            PreForkBeaconState::Deneb(state) => state.tree_hash_root(),
+            PreForkBeaconState::Electra(state) => state.tree_hash_root(),
+        }
+    }
+
+    /// Run `upgrade_to_electra` or `upgrade_to_fulu` on the state.
+    pub fn upgrade(self) -> anyhow::Result<ForkedBeaconState> {
+        Ok(match self {
+            PreForkBeaconState::Deneb(state) => {
+                ForkedBeaconState::Electra(Box::new(state.upgrade_to_electra()?))
+            }
+            PreForkBeaconState::Electra(state) => {
+                ForkedBeaconState::Fulu(Box::new(state.upgrade_to_fulu()?))
+            }
+        })
+    }
+}
@@ -4,7 +4,7 @@ Professional ZisK Per-Operation Cycle Counting Analysis Tool
 ===========================================================
 
 This script analyzes ZisK execution output to calculate per-operation costs.
-It dynamically parses the output without hardcoded values.
+Per-phase step counts come from the PHASE_STEPS markers printed by the guest.
 
 Usage:
     python3 timinganalysis.py [input_file]
@@ -65,21 +65,18 @@ class ZiskTimingAnalyzer:
         self._parse_memory_stats(content)
         
     def _parse_timing_markers(self, content: str) -> None:
-        """Extract timing markers from the output."""
-        timing_pattern = r'TIMING_(START|END):(.+)'
-        matches = re.findall(timing_pattern, content)
-        
-        operation_times = {}
-        for marker_type, operation_name in matches:
-            if operation_name not in operation_times:
-                operation_times[operation_name] = {}
-            operation_times[operation_name][marker_type.lower()] = True
-            
-        # Create OperationTiming objects
-        for op_name, markers in operation_times.items():
-            if 'start' in markers and 'end' in markers:
-                self.operations.append(OperationTiming(name=op_name))
-                
+        """Extract the per-phase step counts reported by the guest."""
+        phase_pattern = r'PHASE_STEPS:([\w-]+):(\d+)'
+        matches = re.findall(phase_pattern, content)
+
+        # A phase entered more than once reports the sum of its runs
+        phase_steps: Dict[str, int] = {}
+        for phase_name, steps in matches:
+            phase_steps[phase_name] = phase_steps.get(phase_name, 0) + int(steps)
+
+        for phase_name, steps in phase_steps.items():
+            self.operations.append(OperationTiming(name=phase_name, steps=steps))
+
     def _parse_execution_stats(self, content: str) -> None:
         """Extract execution statistics from the output."""
         # Parse total steps
@@ -151,35 +148,20 @@ class ZiskTimingAnalyzer:
             }
             
     def calculate_per_operation_costs(self) -> None:
-        """Calculate per-operation costs using complexity-based estimation."""
+        """Attribute duration and cost to each phase in proportion to its measured steps."""
         if not self.execution_stats or not self.operations:
             return
-            
-        # Use complexity-based estimation (most realistic)
-        self._calculate_complexity_based()
-        
-            
-    def _calculate_complexity_based(self) -> None:
-        """Calculate costs based on operation complexity estimates."""
-        if not self.execution_stats or not self.operations:
+
+        total_steps = self.execution_stats.total_steps
+        if total_steps == 0:
             return
-            
-        # Complexity weights based on typical operation costs
-        complexity_weights = {
-            'deserialize-pre-state-ssz': 0.15,      # SSZ deserialization
-            'deserialize-operation-input': 0.05,    # Simple deserialization  
-            'process-operation': 0.70,              # Main processing (most expensive)
-            'merkleize-operation': 0.08,            # Tree hashing
-            'output-state-root': 0.02               # Simple output
-        }
-        
+
         for op in self.operations:
-            weight = complexity_weights.get(op.name, 0.2)  # Default 20% if unknown
-            op.steps = int(self.execution_stats.total_steps * weight)
-            op.duration = self.execution_stats.total_duration * weight
-            op.cost = self.execution_stats.total_cost * weight
-            
-            
+            share = op.steps / total_steps
+            op.duration = self.execution_stats.total_duration * share
+            op.cost = self.execution_stats.total_cost * share
+
+
     def generate_report(self) -> str:
         """Generate a comprehensive analysis report."""
         if not self.execution_stats:
@@ -214,28 +196,16 @@ class ZiskTimingAnalyzer:
             report.append("PER-OPERATION COST ANALYSIS")
             report.append("-" * 40)
             
-            # Complexity-Based Estimation (most realistic)
-            complexity_weights = {
-                'deserialize-pre-state-ssz': 0.15,
-                'deserialize-operation-input': 0.05,
-                'process-operation': 0.70,
-                'merkleize-operation': 0.08,
-                'output-state-root': 0.02
-            }
-            
             for op in self.operations:
-                weight = complexity_weights.get(op.name, 0.2)
-                op_steps = int(self.execution_stats.total_steps * weight)
-                op_time = self.execution_stats.total_duration * weight
-                op_cost = self.execution_stats.total_cost * weight
-                
+                share = op.steps / self.execution_stats.total_steps if self.execution_stats.total_steps else 0.0
+
                 report.append(f"{op.name}:")
-                report.append(f"  Weight: {weight*100:.0f}%")
-                report.append(f"  Steps: {op_steps:,}")
-                report.append(f"  Time: {op_time:.4f} seconds")
-                report.append(f"  Cost: {op_cost:.2f} sec")
+                report.append(f"  Share: {share*100:.2f}%")
+                report.append(f"  Steps: {op.steps:,}")
+                report.append(f"  Time: {op.duration or 0.0:.4f} seconds")
+                report.append(f"  Cost: {op.cost or 0.0:.2f} sec")
                 report.append("")
-                
+
         # Opcode analysis
         if self.opcode_stats:
             report.append("TOP EXPENSIVE OPCODES")